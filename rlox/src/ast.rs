@@ -33,7 +33,39 @@ pub enum Expr {
         args: Vec<Expr>,
         line: u64,
     },
+    Get {
+        object: Box<Expr>,
+        name: String,
+        line: u64,
+    },
     Grouping(Box<Expr>),
+    Index {
+        object: Box<Expr>,
+        index: Box<Expr>,
+        line: u64,
+    },
+    Lambda {
+        params: Vec<String>,
+        body: Vec<Stmt>,
+        line: u64,
+        // Written as `(params) => expr`, in which case the body is a single
+        // `return expr;`
+        arrow: bool,
+    },
+    List(Vec<Expr>),
+    Map(Vec<(Expr, Expr)>, u64),
+    Set {
+        object: Box<Expr>,
+        name: String,
+        value: Box<Expr>,
+        line: u64,
+    },
+    SetIndex {
+        object: Box<Expr>,
+        index: Box<Expr>,
+        value: Box<Expr>,
+        line: u64,
+    },
     Unary(UnOp, Box<Expr>, u64),
     Literal(Lit),
     Logical {
@@ -65,22 +97,59 @@ pub enum Lit {
 pub enum Stmt {
     Expr(Expr),
     Block(Vec<Stmt>),
+    Break(u64),
+    Continue(u64),
+    // `import "path" as alias;` or `from "path" import names;`
+    Import {
+        path: String,
+        alias: Option<String>,
+        names: Vec<String>,
+        line: u64,
+    },
     If {
         condition: Expr,
         then: Box<Stmt>,
         r#else: Option<Box<Stmt>>,
     },
+    // `for (initializer; condition; increment)`. The increment is kept
+    // apart from the body so that `continue` doesn't skip it.
+    For {
+        initializer: Option<Box<Stmt>>,
+        condition: Option<Expr>,
+        increment: Option<Expr>,
+        body: Box<Stmt>,
+    },
+    // `for (name in iterable)`, or `for (name in iterable..range_end)`
+    ForIn {
+        name: String,
+        iterable: Expr,
+        range_end: Option<Expr>,
+        body: Box<Stmt>,
+        line: u64,
+    },
     Function {
         name: String,
         // parameter names
         params: Vec<String>,
         body: Vec<Stmt>,
         line: u64,
+        // `///` comments preceding the declaration
+        doc: Option<String>,
     },
     Print(Expr),
     Return(Option<Expr>, u64),
+    Throw(Expr, u64),
+    Try {
+        body: Vec<Stmt>,
+        // The name the caught error is bound to, and the handler
+        catch: Option<(String, Vec<Stmt>)>,
+        finally: Option<Vec<Stmt>>,
+    },
     Var(String, Option<Expr>),
-    While(Expr, Box<Stmt>),
+    While {
+        condition: Expr,
+        body: Box<Stmt>,
+    },
 }
 
 // Unary Operators
@@ -88,6 +157,8 @@ pub enum Stmt {
 pub enum UnOp {
     Neg,
     Not,
+    // Implicit conversion to string, produced by string interpolation.
+    Str,
 }
 
 impl Expr {
@@ -112,10 +183,44 @@ impl Expr {
         }
     }
 
+    pub fn get(object: Expr, name: String, line: u64) -> Expr {
+        Expr::Get {
+            object: Box::new(object),
+            name,
+            line,
+        }
+    }
+
     pub fn grouping(expr: Expr) -> Expr {
         Expr::Grouping(Box::new(expr))
     }
 
+    pub fn index(object: Expr, index: Expr, line: u64) -> Expr {
+        Expr::Index {
+            object: Box::new(object),
+            index: Box::new(index),
+            line,
+        }
+    }
+
+    pub fn set(object: Expr, name: String, value: Expr, line: u64) -> Expr {
+        Expr::Set {
+            object: Box::new(object),
+            name,
+            value: Box::new(value),
+            line,
+        }
+    }
+
+    pub fn set_index(object: Expr, index: Expr, value: Expr, line: u64) -> Expr {
+        Expr::SetIndex {
+            object: Box::new(object),
+            index: Box::new(index),
+            value: Box::new(value),
+            line,
+        }
+    }
+
     pub fn logical(lhs: Expr, kw: Keyword, rhs: Expr, line: u64) -> Expr {
         Expr::Logical {
             lhs: Box::new(lhs),
@@ -128,6 +233,62 @@ impl Expr {
     pub fn unary(op: UnOp, rhs: Expr, line: u64) -> Expr {
         Expr::Unary(op, Box::new(rhs), line)
     }
+
+    /// The line the expression is on, if it or a part of it records one.
+    /// Literals don't.
+    pub fn line(&self) -> Option<u64> {
+        match self {
+            Expr::Assign(.., line)
+            | Expr::Binary { line, .. }
+            | Expr::Call { line, .. }
+            | Expr::Get { line, .. }
+            | Expr::Index { line, .. }
+            | Expr::Lambda { line, .. }
+            | Expr::Map(_, line)
+            | Expr::Set { line, .. }
+            | Expr::SetIndex { line, .. }
+            | Expr::Unary(.., line)
+            | Expr::Logical { line, .. }
+            | Expr::Variable(_, line) => Some(*line),
+            Expr::Grouping(expr) => expr.line(),
+            Expr::List(elements) => elements.iter().find_map(Expr::line),
+            Expr::Literal(_) => None,
+        }
+    }
+}
+
+impl Stmt {
+    /// A line the statement is on, taken from the first part of it that
+    /// records one.
+    pub fn line(&self) -> Option<u64> {
+        match self {
+            Stmt::Expr(expr) | Stmt::Print(expr) => expr.line(),
+            Stmt::Block(stmts) | Stmt::Try { body: stmts, .. } => stmts.iter().find_map(Stmt::line),
+            Stmt::Break(line)
+            | Stmt::Continue(line)
+            | Stmt::Import { line, .. }
+            | Stmt::ForIn { line, .. }
+            | Stmt::Function { line, .. }
+            | Stmt::Return(_, line)
+            | Stmt::Throw(_, line) => Some(*line),
+            Stmt::If {
+                condition, then, ..
+            } => condition.line().or_else(|| then.line()),
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => initializer
+                .as_ref()
+                .and_then(|stmt| stmt.line())
+                .or_else(|| condition.as_ref().and_then(Expr::line))
+                .or_else(|| increment.as_ref().and_then(Expr::line))
+                .or_else(|| body.line()),
+            Stmt::Var(_, init) => init.as_ref().and_then(Expr::line),
+            Stmt::While { condition, body } => condition.line().or_else(|| body.line()),
+        }
+    }
 }
 
 impl From<TokenKind<'_>> for BinOp {
@@ -203,7 +364,7 @@ impl fmt::Display for Keyword {
 impl fmt::Display for Lit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Lit::Str(s) => f.write_str(&s),
+            Lit::Str(s) => f.write_str(s),
             Lit::Num(n) => write!(f, "{:.1}", n),
             Lit::Bool(b) => write!(f, "{}", b),
             Lit::Nil => f.write_str("nil"),
@@ -216,6 +377,7 @@ impl fmt::Display for UnOp {
         f.write_str(match self {
             UnOp::Neg => "-",
             UnOp::Not => "!",
+            UnOp::Str => "str",
         })
     }
 }
@@ -1,5 +1,4 @@
 use std::fmt;
-use std::time::SystemTime;
 
 use crate::value::{Callable, Value};
 use crate::{Interpreter, LoxError};
@@ -12,13 +11,8 @@ impl Callable for Clock {
         0
     }
 
-    fn call(&self, _: &mut Interpreter, _: Vec<Value>) -> Result<Value, LoxError> {
-        let t = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .expect("There is no time")
-            .as_secs();
-
-        Ok(Value::Num(t as f64))
+    fn call(&self, interpreter: &mut Interpreter, _: Vec<Value>) -> Result<Value, LoxError> {
+        Ok(Value::Num(interpreter.host().clock()))
     }
 }
 
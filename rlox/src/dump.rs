@@ -0,0 +1,1085 @@
+use std::fmt::{self, Write};
+
+use crate::ast::{BinOp, Expr, Keyword, Lit, Stmt, UnOp};
+use crate::parser::Layout;
+
+/// Version of the JSON output. It only changes when a change to the
+/// format could break a program reading it.
+pub const VERSION: u32 = 1;
+
+/// The syntax tree of a program, with the lines each node came from, in a
+/// form that can be printed as JSON or as S-expressions.
+///
+/// The JSON is an object holding `version` and `body`, the list of the
+/// program's statements. Every node of the tree is an object whose `type`
+/// names its `ast::Stmt` or `ast::Expr` variant, followed by `span`, the
+/// first and last line it covers, and then its fields under the names they
+/// have in `ast.rs`. Statements also have the `column` they start at,
+/// counted in characters from 1. Expressions only know the line they are
+/// on, and some, such as literals and groupings, don't know that, so they
+/// have no span. `ForIn`, `Function` and `Return` statements also have the
+/// `line` the interpreter keeps for them, which need not be where they
+/// start.
+///
+/// The values of fields are:
+///
+/// - statements and expressions, as objects, or `null` where optional
+/// - names of variables, functions and fields, as strings
+/// - operators, as they are written, so `"+"` or `"and"`; the conversion
+///   to a string in interpolated strings is `"str"`
+/// - the value of a `Literal`, as a JSON value; JSON has no infinity, so a
+///   number too big for a double, like `1e999`, is written as `1e999`,
+///   which reads back as infinity
+/// - `params` and import `names`, as lists of strings
+/// - map `entries`, as a list of `[key, value]` pairs
+/// - a `Try` statement's `catch` and `finally`, as `Catch` and `Finally`
+///   nodes holding a `name` and a `body`, and just a `body`
+///
+/// Interpolated strings appear the way the parser desugars them, as `+`
+/// of string literals and `str` conversions.
+///
+/// The S-expressions hold the same tree. A node prints as `(kind@span
+/// fields...)`, with the kind in kebab-case and a statement's span written
+/// as `line:column-end_line`. Literals print as their value
+/// and missing fields as `nil`. Statements go on lines of their own.
+/// The `line` of a statement is left out.
+///
+/// `load` reads the JSON back into the statements it was made from.
+#[derive(Debug)]
+pub enum Node {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    // A name or operator, which S-expressions print without quotes.
+    Name(String),
+    List(Vec<Node>),
+    // A list of statements.
+    Body(Vec<Node>),
+    Tree(Tree),
+}
+
+#[derive(Debug)]
+pub struct Tree {
+    kind: &'static str,
+    span: Option<(u64, u64)>,
+    column: Option<u64>,
+    line: Option<u64>,
+    // Whether S-expressions put it on a line of its own.
+    own_line: bool,
+    fields: Vec<(&'static str, Node)>,
+}
+
+pub fn program(stmts: &[Stmt], layout: &Layout) -> Node {
+    let mut builder = Builder { layout, span: 0 };
+    builder.body(stmts)
+}
+
+struct Builder<'a> {
+    layout: &'a Layout,
+    // The statement spans are in the order the tree is walked in.
+    span: usize,
+}
+
+impl Builder<'_> {
+    fn body(&mut self, stmts: &[Stmt]) -> Node {
+        Node::Body(stmts.iter().map(|stmt| self.stmt(stmt)).collect())
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> Node {
+        let span = self.layout.spans[self.span];
+        self.span += 1;
+
+        let line = match stmt {
+            Stmt::ForIn { line, .. } | Stmt::Function { line, .. } | Stmt::Return(_, line) => {
+                Some(*line)
+            }
+            _ => None,
+        };
+
+        let (kind, fields) = match stmt {
+            Stmt::Expr(expr) => ("Expr", vec![("expr", self.expr(expr))]),
+            Stmt::Block(stmts) => ("Block", vec![("body", self.body(stmts))]),
+            Stmt::Break(_) => ("Break", vec![]),
+            Stmt::Continue(_) => ("Continue", vec![]),
+            Stmt::Import {
+                path, alias, names, ..
+            } => (
+                "Import",
+                vec![
+                    ("path", Node::Str(path.clone())),
+                    ("alias", alias.as_deref().map_or(Node::Null, name)),
+                    ("names", names_list(names)),
+                ],
+            ),
+            Stmt::If {
+                condition,
+                then,
+                r#else,
+            } => (
+                "If",
+                vec![
+                    ("condition", self.expr(condition)),
+                    ("then", self.stmt(then)),
+                    ("else", self.opt_stmt(r#else.as_deref())),
+                ],
+            ),
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => (
+                "For",
+                vec![
+                    ("initializer", inline(self.opt_stmt(initializer.as_deref()))),
+                    ("condition", self.opt_expr(condition.as_ref())),
+                    ("increment", self.opt_expr(increment.as_ref())),
+                    ("body", self.stmt(body)),
+                ],
+            ),
+            Stmt::ForIn {
+                name: var,
+                iterable,
+                range_end,
+                body,
+                ..
+            } => (
+                "ForIn",
+                vec![
+                    ("name", name(var)),
+                    ("iterable", self.expr(iterable)),
+                    ("range_end", self.opt_expr(range_end.as_ref())),
+                    ("body", self.stmt(body)),
+                ],
+            ),
+            Stmt::Function {
+                name: fun,
+                params,
+                body,
+                doc,
+                ..
+            } => (
+                "Function",
+                vec![
+                    ("name", name(fun)),
+                    ("params", names_list(params)),
+                    ("doc", doc.clone().map_or(Node::Null, Node::Str)),
+                    ("body", self.body(body)),
+                ],
+            ),
+            Stmt::Print(expr) => ("Print", vec![("expr", self.expr(expr))]),
+            Stmt::Return(value, _) => ("Return", vec![("value", self.opt_expr(value.as_ref()))]),
+            Stmt::Throw(value, _) => ("Throw", vec![("value", self.expr(value))]),
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                let body = self.body(body);
+                let catch = match catch {
+                    Some((var, handler)) => Node::Tree(Tree {
+                        kind: "Catch",
+                        span: None,
+                        column: None,
+                        line: None,
+                        own_line: true,
+                        fields: vec![("name", name(var)), ("body", self.body(handler))],
+                    }),
+                    None => Node::Null,
+                };
+                let finally = match finally {
+                    Some(stmts) => Node::Tree(Tree {
+                        kind: "Finally",
+                        span: None,
+                        column: None,
+                        line: None,
+                        own_line: true,
+                        fields: vec![("body", self.body(stmts))],
+                    }),
+                    None => Node::Null,
+                };
+
+                (
+                    "Try",
+                    vec![("body", body), ("catch", catch), ("finally", finally)],
+                )
+            }
+            Stmt::Var(var, initializer) => (
+                "Var",
+                vec![
+                    ("name", name(var)),
+                    ("initializer", self.opt_expr(initializer.as_ref())),
+                ],
+            ),
+            Stmt::While { condition, body } => (
+                "While",
+                vec![
+                    ("condition", self.expr(condition)),
+                    ("body", self.stmt(body)),
+                ],
+            ),
+        };
+
+        Node::Tree(Tree {
+            kind,
+            span: Some((span.line, span.end_line)),
+            column: Some(span.column),
+            line,
+            own_line: true,
+            fields,
+        })
+    }
+
+    fn opt_stmt(&mut self, stmt: Option<&Stmt>) -> Node {
+        stmt.map_or(Node::Null, |stmt| self.stmt(stmt))
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Node {
+        let (kind, line, fields) = match expr {
+            Expr::Assign(var, value, line) => (
+                "Assign",
+                Some(*line),
+                vec![("name", name(var)), ("value", self.expr(value))],
+            ),
+            Expr::Binary { lhs, op, rhs, line } => (
+                "Binary",
+                Some(*line),
+                vec![
+                    ("lhs", self.expr(lhs)),
+                    ("op", name(op)),
+                    ("rhs", self.expr(rhs)),
+                ],
+            ),
+            Expr::Call { callee, args, line } => (
+                "Call",
+                Some(*line),
+                vec![("callee", self.expr(callee)), ("args", self.exprs(args))],
+            ),
+            Expr::Get {
+                object,
+                name: field,
+                line,
+            } => (
+                "Get",
+                Some(*line),
+                vec![("object", self.expr(object)), ("name", name(field))],
+            ),
+            Expr::Grouping(expr) => ("Grouping", None, vec![("expr", self.expr(expr))]),
+            Expr::Index {
+                object,
+                index,
+                line,
+            } => (
+                "Index",
+                Some(*line),
+                vec![("object", self.expr(object)), ("index", self.expr(index))],
+            ),
+            Expr::Lambda {
+                params,
+                body,
+                line,
+                arrow,
+            } => (
+                "Lambda",
+                Some(*line),
+                vec![
+                    ("params", names_list(params)),
+                    ("arrow", Node::Bool(*arrow)),
+                    ("body", self.lambda_body(body, *arrow)),
+                ],
+            ),
+            Expr::List(elements) => ("List", None, vec![("elements", self.exprs(elements))]),
+            Expr::Map(entries, line) => {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| Node::List(vec![self.expr(key), self.expr(value)]))
+                    .collect();
+                ("Map", Some(*line), vec![("entries", Node::List(entries))])
+            }
+            Expr::Set {
+                object,
+                name: field,
+                value,
+                line,
+            } => (
+                "Set",
+                Some(*line),
+                vec![
+                    ("object", self.expr(object)),
+                    ("name", name(field)),
+                    ("value", self.expr(value)),
+                ],
+            ),
+            Expr::SetIndex {
+                object,
+                index,
+                value,
+                line,
+            } => (
+                "SetIndex",
+                Some(*line),
+                vec![
+                    ("object", self.expr(object)),
+                    ("index", self.expr(index)),
+                    ("value", self.expr(value)),
+                ],
+            ),
+            Expr::Unary(op, rhs, line) => (
+                "Unary",
+                Some(*line),
+                vec![("op", name(op)), ("rhs", self.expr(rhs))],
+            ),
+            Expr::Literal(lit) => {
+                let value = match lit {
+                    Lit::Bool(b) => Node::Bool(*b),
+                    Lit::Nil => Node::Null,
+                    Lit::Num(n) => Node::Num(*n),
+                    Lit::Str(s) => Node::Str(s.clone()),
+                };
+                ("Literal", None, vec![("value", value)])
+            }
+            Expr::Logical { lhs, kw, rhs, line } => (
+                "Logical",
+                Some(*line),
+                vec![
+                    ("lhs", self.expr(lhs)),
+                    ("kw", name(kw)),
+                    ("rhs", self.expr(rhs)),
+                ],
+            ),
+            Expr::Variable(var, line) => ("Variable", Some(*line), vec![("name", name(var))]),
+        };
+
+        tree(kind, line.map(|line| (line, line)), fields)
+    }
+
+    fn opt_expr(&mut self, expr: Option<&Expr>) -> Node {
+        expr.map_or(Node::Null, |expr| self.expr(expr))
+    }
+
+    fn exprs(&mut self, exprs: &[Expr]) -> Node {
+        Node::List(exprs.iter().map(|expr| self.expr(expr)).collect())
+    }
+
+    // An arrow function's `return` was made up by the parser, so it has no
+    // span of its own. It gets the line of the arrow.
+    fn lambda_body(&mut self, body: &[Stmt], arrow: bool) -> Node {
+        match body {
+            [Stmt::Return(value, line)] if arrow => {
+                let value = self.opt_expr(value.as_ref());
+                Node::Body(vec![Node::Tree(Tree {
+                    kind: "Return",
+                    span: Some((*line, *line)),
+                    column: None,
+                    line: Some(*line),
+                    own_line: true,
+                    fields: vec![("value", value)],
+                })])
+            }
+            _ => self.body(body),
+        }
+    }
+}
+
+fn tree(kind: &'static str, span: Option<(u64, u64)>, fields: Vec<(&'static str, Node)>) -> Node {
+    Node::Tree(Tree {
+        kind,
+        span,
+        column: None,
+        line: None,
+        own_line: false,
+        fields,
+    })
+}
+
+fn inline(mut node: Node) -> Node {
+    if let Node::Tree(tree) = &mut node {
+        tree.own_line = false;
+    }
+    node
+}
+
+fn name(name: &(impl ToString + ?Sized)) -> Node {
+    Node::Name(name.to_string())
+}
+
+fn names_list(names: &[String]) -> Node {
+    Node::List(names.iter().map(name).collect())
+}
+
+impl Node {
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        write!(out, "{{\n  \"version\": {},\n  \"body\": ", VERSION).unwrap();
+        self.json(&mut out, 1);
+        out.push_str("\n}\n");
+        out
+    }
+
+    pub fn to_sexpr(&self) -> String {
+        let mut out = String::new();
+        match self {
+            Node::Body(stmts) => {
+                for stmt in stmts {
+                    stmt.sexpr(&mut out, 0);
+                    out.push('\n');
+                }
+            }
+            node => node.sexpr(&mut out, 0),
+        }
+        out
+    }
+
+    fn json(&self, out: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth);
+
+        match self {
+            Node::Null => out.push_str("null"),
+            Node::Bool(b) => write!(out, "{}", b).unwrap(),
+            Node::Num(n) if n.is_infinite() => {
+                out.push_str(if *n > 0.0 { "1e999" } else { "-1e999" })
+            }
+            Node::Num(n) => write!(out, "{}", n).unwrap(),
+            Node::Str(s) | Node::Name(s) => quote(out, s),
+            Node::List(items) | Node::Body(items) if items.is_empty() => out.push_str("[]"),
+            Node::List(items) | Node::Body(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write!(out, "\n{}  ", indent).unwrap();
+                    item.json(out, depth + 1);
+                }
+                write!(out, "\n{}]", indent).unwrap();
+            }
+            Node::Tree(tree) => {
+                write!(out, "{{\n{}  \"type\": \"{}\"", indent, tree.kind).unwrap();
+                if let Some((start, end)) = tree.span {
+                    write!(out, ",\n{}  \"span\": [{}, {}]", indent, start, end).unwrap();
+                }
+                if let Some(column) = tree.column {
+                    write!(out, ",\n{}  \"column\": {}", indent, column).unwrap();
+                }
+                if let Some(line) = tree.line {
+                    write!(out, ",\n{}  \"line\": {}", indent, line).unwrap();
+                }
+                for (field, value) in &tree.fields {
+                    write!(out, ",\n{}  \"{}\": ", indent, field).unwrap();
+                    value.json(out, depth + 1);
+                }
+                write!(out, "\n{}}}", indent).unwrap();
+            }
+        }
+    }
+
+    fn sexpr(&self, out: &mut String, depth: usize) {
+        match self {
+            Node::Null => out.push_str("nil"),
+            Node::Bool(b) => write!(out, "{}", b).unwrap(),
+            Node::Num(n) => write!(out, "{}", n).unwrap(),
+            Node::Str(s) => quote(out, s),
+            Node::Name(s) => out.push_str(s),
+            Node::List(items) => {
+                out.push('(');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+                    item.sexpr(out, depth);
+                }
+                out.push(')');
+            }
+            // Printed as part of the node that holds it.
+            Node::Body(stmts) => {
+                for (i, stmt) in stmts.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+                    stmt.sexpr(out, depth);
+                }
+            }
+            Node::Tree(tree) if tree.kind == "Literal" => tree.fields[0].1.sexpr(out, depth),
+            Node::Tree(tree) => {
+                out.push('(');
+                out.push_str(&kebab_case(tree.kind));
+                if let Some((start, end)) = tree.span {
+                    write!(out, "@{}", start).unwrap();
+                    if let Some(column) = tree.column {
+                        write!(out, ":{}", column).unwrap();
+                    }
+                    if end != start {
+                        write!(out, "-{}", end).unwrap();
+                    }
+                }
+
+                for (_, value) in &tree.fields {
+                    match value {
+                        Node::Body(stmts) => {
+                            for stmt in stmts {
+                                newline(out, depth + 1);
+                                stmt.sexpr(out, depth + 1);
+                            }
+                        }
+                        Node::Tree(Tree { own_line: true, .. }) => {
+                            newline(out, depth + 1);
+                            value.sexpr(out, depth + 1);
+                        }
+                        _ => {
+                            out.push(' ');
+                            value.sexpr(out, depth);
+                        }
+                    }
+                }
+                out.push(')');
+            }
+        }
+    }
+}
+
+fn newline(out: &mut String, depth: usize) {
+    out.push('\n');
+    out.push_str(&"  ".repeat(depth));
+}
+
+fn kebab_case(kind: &str) -> String {
+    let mut s = String::new();
+    for (i, c) in kind.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            s.push('-');
+        }
+        s.push(c.to_ascii_lowercase());
+    }
+    s
+}
+
+fn quote(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Reads the JSON printed by `Node::to_json` back into the statements it
+/// was made from.
+pub fn load(json: &str) -> Result<Vec<Stmt>, String> {
+    let mut reader = Reader { src: json, pos: 0 };
+    let program = reader.value()?;
+    reader.skip_space();
+    if reader.pos < json.len() {
+        return Err(reader.error("Expect end of input"));
+    }
+
+    match program.get("version")? {
+        Json::Num(version) if *version == VERSION as f64 => body(program.get("body")?),
+        _ => Err(format!("Only version {} can be loaded.", VERSION)),
+    }
+}
+
+fn body(node: &Json) -> Result<Vec<Stmt>, String> {
+    node.list()?.iter().map(stmt).collect()
+}
+
+fn stmt(node: &Json) -> Result<Stmt, String> {
+    let stmt = match node.kind()? {
+        "Expr" => Stmt::Expr(expr(node.get("expr")?)?),
+        "Block" => Stmt::Block(body(node.get("body")?)?),
+        "Break" => Stmt::Break(node.start_line()?),
+        "Continue" => Stmt::Continue(node.start_line()?),
+        "Import" => Stmt::Import {
+            path: node.get("path")?.string()?,
+            alias: opt(node.get("alias")?, Json::string)?,
+            names: node.get("names")?.strings()?,
+            line: node.start_line()?,
+        },
+        "If" => Stmt::If {
+            condition: expr(node.get("condition")?)?,
+            then: Box::new(stmt(node.get("then")?)?),
+            r#else: opt(node.get("else")?, stmt)?.map(Box::new),
+        },
+        "For" => Stmt::For {
+            initializer: opt(node.get("initializer")?, stmt)?.map(Box::new),
+            condition: opt(node.get("condition")?, expr)?,
+            increment: opt(node.get("increment")?, expr)?,
+            body: Box::new(stmt(node.get("body")?)?),
+        },
+        "ForIn" => Stmt::ForIn {
+            name: node.get("name")?.string()?,
+            iterable: expr(node.get("iterable")?)?,
+            range_end: opt(node.get("range_end")?, expr)?,
+            body: Box::new(stmt(node.get("body")?)?),
+            line: node.get("line")?.line()?,
+        },
+        "Function" => Stmt::Function {
+            name: node.get("name")?.string()?,
+            params: node.get("params")?.strings()?,
+            body: body(node.get("body")?)?,
+            line: node.get("line")?.line()?,
+            doc: opt(node.get("doc")?, Json::string)?,
+        },
+        "Print" => Stmt::Print(expr(node.get("expr")?)?),
+        "Return" => Stmt::Return(opt(node.get("value")?, expr)?, node.get("line")?.line()?),
+        "Throw" => Stmt::Throw(expr(node.get("value")?)?, node.start_line()?),
+        "Try" => Stmt::Try {
+            body: body(node.get("body")?)?,
+            catch: opt(node.get("catch")?, |catch| {
+                Ok((catch.get("name")?.string()?, body(catch.get("body")?)?))
+            })?,
+            finally: opt(node.get("finally")?, |finally| body(finally.get("body")?))?,
+        },
+        "Var" => Stmt::Var(
+            node.get("name")?.string()?,
+            opt(node.get("initializer")?, expr)?,
+        ),
+        "While" => Stmt::While {
+            condition: expr(node.get("condition")?)?,
+            body: Box::new(stmt(node.get("body")?)?),
+        },
+        kind => return Err(format!("Unknown statement type '{}'.", kind)),
+    };
+
+    Ok(stmt)
+}
+
+fn expr(node: &Json) -> Result<Expr, String> {
+    let sub = |field| expr(node.get(field)?);
+
+    let expr = match node.kind()? {
+        "Assign" => Expr::assign(
+            node.get("name")?.string()?,
+            sub("value")?,
+            node.start_line()?,
+        ),
+        "Binary" => Expr::binary(
+            sub("lhs")?,
+            op(node.get("op")?, &BIN_OPS)?,
+            sub("rhs")?,
+            node.start_line()?,
+        ),
+        "Call" => Expr::call(
+            sub("callee")?,
+            exprs(node.get("args")?)?,
+            node.start_line()?,
+        ),
+        "Get" => Expr::get(
+            sub("object")?,
+            node.get("name")?.string()?,
+            node.start_line()?,
+        ),
+        "Grouping" => Expr::grouping(sub("expr")?),
+        "Index" => Expr::index(sub("object")?, sub("index")?, node.start_line()?),
+        "Lambda" => Expr::Lambda {
+            params: node.get("params")?.strings()?,
+            body: body(node.get("body")?)?,
+            line: node.start_line()?,
+            arrow: match node.get("arrow")? {
+                Json::Bool(arrow) => *arrow,
+                _ => return Err("Expect a boolean for 'arrow'.".into()),
+            },
+        },
+        "List" => Expr::List(exprs(node.get("elements")?)?),
+        "Map" => {
+            let entries = node
+                .get("entries")?
+                .list()?
+                .iter()
+                .map(|entry| match entry.list()? {
+                    [key, value] => Ok((expr(key)?, expr(value)?)),
+                    _ => Err("Expect a map entry to be a [key, value] pair.".to_string()),
+                });
+            Expr::Map(entries.collect::<Result<_, _>>()?, node.start_line()?)
+        }
+        "Set" => Expr::set(
+            sub("object")?,
+            node.get("name")?.string()?,
+            sub("value")?,
+            node.start_line()?,
+        ),
+        "SetIndex" => Expr::set_index(
+            sub("object")?,
+            sub("index")?,
+            sub("value")?,
+            node.start_line()?,
+        ),
+        "Unary" => Expr::unary(
+            op(node.get("op")?, &UN_OPS)?,
+            sub("rhs")?,
+            node.start_line()?,
+        ),
+        "Literal" => Expr::Literal(match node.get("value")? {
+            Json::Null => Lit::Nil,
+            Json::Bool(b) => Lit::Bool(*b),
+            Json::Num(n) => Lit::Num(*n),
+            Json::Str(s) => Lit::Str(s.clone()),
+            _ => {
+                return Err("Expect a literal to be null, a boolean, a number or a string.".into())
+            }
+        }),
+        "Logical" => Expr::logical(
+            sub("lhs")?,
+            op(node.get("kw")?, &[Keyword::And, Keyword::Or])?,
+            sub("rhs")?,
+            node.start_line()?,
+        ),
+        "Variable" => Expr::Variable(node.get("name")?.string()?, node.start_line()?),
+        kind => return Err(format!("Unknown expression type '{}'.", kind)),
+    };
+
+    Ok(expr)
+}
+
+fn exprs(node: &Json) -> Result<Vec<Expr>, String> {
+    node.list()?.iter().map(expr).collect()
+}
+
+fn opt<T>(node: &Json, load: impl FnOnce(&Json) -> Result<T, String>) -> Result<Option<T>, String> {
+    match node {
+        Json::Null => Ok(None),
+        node => load(node).map(Some),
+    }
+}
+
+const BIN_OPS: [BinOp; 10] = [
+    BinOp::Add,
+    BinOp::Div,
+    BinOp::Eq,
+    BinOp::Gt,
+    BinOp::GtEq,
+    BinOp::Lt,
+    BinOp::LtEq,
+    BinOp::Mul,
+    BinOp::NotEq,
+    BinOp::Sub,
+];
+
+const UN_OPS: [UnOp; 3] = [UnOp::Neg, UnOp::Not, UnOp::Str];
+
+// Finds the operator that is written as the string in `node`.
+fn op<T: Copy + fmt::Display>(node: &Json, ops: &[T]) -> Result<T, String> {
+    let name = node.string()?;
+    ops.iter()
+        .copied()
+        .find(|op| op.to_string() == name)
+        .ok_or_else(|| format!("Unknown operator '{}'.", name))
+}
+
+enum Json {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    List(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, field: &str) -> Result<&Json, String> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == field)
+                .map(|(_, value)| value)
+                .ok_or_else(|| format!("Missing field '{}'.", field)),
+            _ => Err(format!("Expect an object with a '{}' field.", field)),
+        }
+    }
+
+    fn kind(&self) -> Result<&str, String> {
+        match self.get("type")? {
+            Json::Str(kind) => Ok(kind),
+            _ => Err("Expect a string for 'type'.".into()),
+        }
+    }
+
+    fn string(&self) -> Result<String, String> {
+        match self {
+            Json::Str(s) => Ok(s.clone()),
+            _ => Err("Expect a string.".into()),
+        }
+    }
+
+    fn strings(&self) -> Result<Vec<String>, String> {
+        self.list()?.iter().map(Json::string).collect()
+    }
+
+    fn list(&self) -> Result<&[Json], String> {
+        match self {
+            Json::List(items) => Ok(items),
+            _ => Err("Expect a list.".into()),
+        }
+    }
+
+    fn line(&self) -> Result<u64, String> {
+        match self {
+            Json::Num(n) if *n >= 0.0 && n.fract() == 0.0 => Ok(*n as u64),
+            _ => Err("Expect a line number.".into()),
+        }
+    }
+
+    fn start_line(&self) -> Result<u64, String> {
+        match self.get("span")?.list()? {
+            [start, _] => start.line(),
+            _ => Err("Expect a span to be a [start, end] pair.".into()),
+        }
+    }
+}
+
+// Just enough of a JSON parser to read back what `Node::to_json` prints.
+struct Reader<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_space();
+
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.list(),
+            Some('"') => self.string().map(Json::Str),
+            Some('-' | '0'..='9') => self.number(),
+            _ if self.word("null") => Ok(Json::Null),
+            _ if self.word("true") => Ok(Json::Bool(true)),
+            _ if self.word("false") => Ok(Json::Bool(false)),
+            _ => Err(self.error("Expect a value")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        let mut fields = vec![];
+        self.advance();
+
+        if !self.eat('}') {
+            loop {
+                self.skip_space();
+                if self.peek() != Some('"') {
+                    return Err(self.error("Expect a field name"));
+                }
+                let name = self.string()?;
+                if !self.eat(':') {
+                    return Err(self.error("Expect ':' after field name"));
+                }
+                fields.push((name, self.value()?));
+
+                if self.eat('}') {
+                    break;
+                }
+                if !self.eat(',') {
+                    return Err(self.error("Expect ',' or '}' after field"));
+                }
+            }
+        }
+
+        Ok(Json::Object(fields))
+    }
+
+    fn list(&mut self) -> Result<Json, String> {
+        let mut items = vec![];
+        self.advance();
+
+        if !self.eat(']') {
+            loop {
+                items.push(self.value()?);
+
+                if self.eat(']') {
+                    break;
+                }
+                if !self.eat(',') {
+                    return Err(self.error("Expect ',' or ']' after list item"));
+                }
+            }
+        }
+
+        Ok(Json::List(items))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let mut s = String::new();
+        self.advance();
+
+        loop {
+            match self.advance() {
+                None => return Err(self.error("Unterminated string")),
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let c = match self.advance() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => self.unicode_escape()?,
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        _ => return Err(self.error("Invalid escape sequence")),
+                    };
+                    s.push(c);
+                }
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    // The part of `\uXXXX` after the `u`. Characters outside the Basic
+    // Multilingual Plane are written as a pair of them.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.word("\\u") {
+                return Err(self.error("Expect a low surrogate"));
+            }
+            let low = self.hex4()?;
+            0x10000 + ((high - 0xD800) << 10) + low.wrapping_sub(0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("Invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self.src.get(self.pos..self.pos + 4);
+        match digits.and_then(|digits| u32::from_str_radix(digits, 16).ok()) {
+            Some(code) => {
+                self.pos += 4;
+                Ok(code)
+            }
+            None => Err(self.error("Expect four hex digits")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while let Some('-' | '+' | '.' | 'e' | 'E' | '0'..='9') = self.peek() {
+            self.advance();
+        }
+
+        self.src[start..self.pos]
+            .parse()
+            .map(Json::Num)
+            .map_err(|_| self.error("Invalid number"))
+    }
+
+    fn word(&mut self, word: &str) -> bool {
+        let found = self.src[self.pos..].starts_with(word);
+        if found {
+            self.pos += word.len();
+        }
+        found
+    }
+
+    // Skips whitespace, then consumes `c` if it comes next.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_space();
+        let found = self.peek() == Some(c);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn skip_space(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.advance();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, msg: &str) -> String {
+        format!("{} at byte {}.", msg, self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+    use crate::parser;
+
+    fn lox_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                lox_files(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == "lox") {
+                files.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let mut files = vec![];
+        lox_files(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("../test"),
+            &mut files,
+        );
+
+        let mut loaded = 0;
+        for path in files {
+            let source = match fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(_) => continue,
+            };
+            let (stmts, layout, errors) = parser::parse_with_layout(&source);
+            if !errors.is_empty() {
+                continue;
+            }
+
+            let json = program(&stmts, &layout).to_json();
+            let load = load(&json).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            assert_eq!(load, stmts, "{}", path.display());
+            loaded += 1;
+        }
+
+        assert!(loaded > 100, "only {} files parsed", loaded);
+    }
+
+    #[test]
+    fn json_round_trips_escapes() {
+        let source = "/// \"doc\"\nfun f(a) { return \"\\u{1}\\t\\\\ \\u{1F600} ${a}\"; }";
+        let (stmts, layout, errors) = parser::parse_with_layout(source);
+        assert!(errors.is_empty());
+
+        let json = program(&stmts, &layout).to_json();
+        assert_eq!(load(&json).unwrap(), stmts);
+    }
+
+    #[test]
+    fn json_round_trips_infinity() {
+        let (stmts, layout, errors) = parser::parse_with_layout("print 1e999; print -1e999;");
+        assert!(errors.is_empty());
+
+        let json = program(&stmts, &layout).to_json();
+        assert!(!json.contains("inf"));
+        assert_eq!(load(&json).unwrap(), stmts);
+    }
+
+    #[test]
+    fn load_rejects_bad_trees() {
+        assert!(load("{\"version\": 2, \"body\": []}").is_err());
+        assert!(load("{\"version\": 1, \"body\": [{\"type\": \"Nope\"}]}").is_err());
+        assert!(load("{\"version\": 1, \"body\": [").is_err());
+    }
+}
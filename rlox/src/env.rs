@@ -39,6 +39,11 @@ impl Env {
         }
     }
 
+    // Looks `name` up in this environment only, ignoring enclosing ones.
+    pub fn get_own(&self, name: &str) -> Option<Value> {
+        self.values.get(name).cloned()
+    }
+
     pub fn assign(&mut self, name: &str, value: Value) -> Result<(), String> {
         if let Some(v) = self.values.get_mut(name) {
             *v = value;
@@ -7,6 +7,24 @@ pub enum LoxError {
     Runtime(String, u64),
     // This variant carries return values
     Return(Value),
+    // These unwind to the innermost enclosing loop
+    Break,
+    Continue,
+    // A value raised by `throw`, and the line it was thrown from
+    Throw(Value, u64),
+    // Raised by the `exit` native to stop the program with a status code
+    Exit(i32),
+    // A resource limit was hit or the program was interrupted. Unlike
+    // `Runtime`, scripts can't catch it.
+    Limit(String, u64),
+}
+
+impl LoxError {
+    // Natives don't know which line called them, so they leave it at 0 and
+    // the call expression fills it in.
+    pub fn native(msg: impl Into<String>) -> Self {
+        LoxError::Runtime(msg.into(), 0)
+    }
 }
 
 impl fmt::Display for LoxError {
@@ -19,6 +37,11 @@ impl fmt::Display for LoxError {
             LoxError::Return(v) => {
                 write!(f, "RET({})", v)
             }
+            LoxError::Break => f.write_str("BREAK"),
+            LoxError::Continue => f.write_str("CONTINUE"),
+            LoxError::Throw(v, ..) => write!(f, "{}", v),
+            LoxError::Exit(code) => write!(f, "EXIT({})", code),
+            LoxError::Limit(msg, ..) => f.write_str(msg),
         }
     }
 }
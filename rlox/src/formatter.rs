@@ -0,0 +1,726 @@
+use std::mem;
+
+use crate::ast::{BinOp, Expr, Keyword, Lit, Stmt, UnOp};
+use crate::parser::{Layout, Span};
+use crate::visitor::{ExprVisitor, StmtVisitor};
+
+const INDENT: &str = "  ";
+const WIDTH: usize = 80;
+
+/// Prints a program back out in the canonical style, keeping the comments
+/// and single blank lines of the source it was parsed from.
+///
+/// Blocks open on the line of their statement and are indented by two
+/// spaces. Argument lists, list literals and map literals that don't fit in
+/// `WIDTH` columns get one item per line.
+pub struct Formatter {
+    out: String,
+    indent: usize,
+    layout: Layout,
+    // How far into each part of the layout printing has got. The tree is
+    // walked in source order, so these only move forward, except when a
+    // line is printed again to wrap it.
+    span: usize,
+    block_end: usize,
+    comment: usize,
+    literal: usize,
+    interpolation: usize,
+    // The source line the last thing printed on its own line ended on.
+    last_line: u64,
+    // Nothing has been printed in the current block yet.
+    fresh: bool,
+    // Some list of items had to be wrapped.
+    wrapped: bool,
+    // Inside a `${}` segment, which has to stay on one line.
+    no_wrap: bool,
+}
+
+// Where printing was, so that it can go back there.
+struct Mark {
+    len: usize,
+    span: usize,
+    block_end: usize,
+    comment: usize,
+    literal: usize,
+    interpolation: usize,
+    last_line: u64,
+    fresh: bool,
+}
+
+pub fn format(stmts: &[Stmt], layout: Layout) -> String {
+    let mut formatter = Formatter {
+        out: String::new(),
+        indent: 0,
+        layout,
+        span: 0,
+        block_end: 0,
+        comment: 0,
+        literal: 0,
+        interpolation: 0,
+        last_line: 0,
+        fresh: true,
+        wrapped: false,
+        no_wrap: false,
+    };
+
+    for stmt in stmts {
+        formatter.statement(stmt);
+    }
+    formatter.comments_before(u64::MAX, 0);
+
+    if !formatter.out.is_empty() {
+        formatter.out.push('\n');
+    }
+    formatter.out
+}
+
+impl Formatter {
+    // Prints a statement on a line of its own, after any comments that come
+    // before it. A block comment that ends where the statement starts stays
+    // in front of it.
+    fn statement(&mut self, stmt: &Stmt) {
+        let span = self.next_span();
+
+        if self.comments_before(span.line, span.column) {
+            self.out.push(' ');
+        } else {
+            if !self.fresh && span.line > self.last_line + 1 {
+                self.out.push('\n');
+            }
+            self.newline();
+        }
+        self.visit_stmt(stmt);
+
+        self.last_line = self.last_line.max(span.end_line);
+        self.fresh = false;
+    }
+
+    // Prints the body of an `if` or a loop on the line it started on.
+    fn body(&mut self, stmt: &Stmt) {
+        self.next_span();
+        self.out.push(' ');
+        self.visit_stmt(stmt);
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) {
+        self.out.push('{');
+        let open = self.out.len();
+
+        self.indent += 1;
+        self.fresh = true;
+        for stmt in stmts {
+            self.statement(stmt);
+        }
+
+        let (end, column) = self.layout.block_ends[self.block_end];
+        self.block_end += 1;
+        self.comments_before(end, column);
+        self.indent -= 1;
+
+        if self.out.len() > open {
+            self.newline();
+        }
+        self.out.push('}');
+
+        self.last_line = self.last_line.max(end);
+        self.fresh = false;
+    }
+
+    fn next_span(&mut self) -> Span {
+        let span = self.layout.spans[self.span];
+        self.span += 1;
+        span
+    }
+
+    // Prints the next number or string literal the way it was written, so
+    // that `0x1F` stays as it is rather than turning into 31.
+    fn next_literal(&mut self) {
+        self.out.push_str(&self.layout.literals[self.literal].text);
+        self.literal += 1;
+    }
+
+    // Prints the block comments that come before the next literal where
+    // they are, as in `var x = /* seconds */ 60;`. A line comment can't be,
+    // so it and anything after it waits for the next statement.
+    fn inline_comments(&mut self) {
+        let Some(literal) = self.layout.literals.get(self.literal) else {
+            return;
+        };
+        let at = (literal.line, literal.column);
+
+        while let Some(comment) = self.layout.comments.get(self.comment) {
+            if (comment.line, comment.column) >= at || !comment.text.starts_with("/*") {
+                break;
+            }
+            self.out.push_str(&comment.text);
+            self.out.push(' ');
+            self.comment += 1;
+        }
+    }
+
+    // Prints the comments that start before the given line and column. One
+    // that followed code in the source is put at the end of the line printed
+    // last. Returns whether the last one is a block comment that ends on
+    // `line`, so that what follows it can go on the same line.
+    fn comments_before(&mut self, line: u64, column: u64) -> bool {
+        let mut inline = false;
+
+        while let Some(comment) = self.layout.comments.get(self.comment).cloned() {
+            if (comment.line, comment.column) >= (line, column) {
+                break;
+            }
+
+            if comment.trailing && !self.out.is_empty() {
+                self.out.push(' ');
+            } else {
+                if !self.fresh && comment.line > self.last_line + 1 {
+                    self.out.push('\n');
+                }
+                self.newline();
+                self.fresh = false;
+            }
+
+            self.out.push_str(&comment.text);
+            self.last_line = self.last_line.max(comment.end_line);
+            self.comment += 1;
+
+            inline =
+                !comment.trailing && comment.end_line == line && comment.text.starts_with("/*");
+        }
+        inline
+    }
+
+    fn newline(&mut self) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    // Prints `items` between `open` and `close` on one line if they fit, or
+    // else one per line.
+    fn items<T>(&mut self, open: &str, items: &[T], close: &str, item: fn(&mut Self, &T)) {
+        let mark = self.mark();
+        let wrapped = mem::replace(&mut self.wrapped, false);
+
+        self.out.push_str(open);
+        for (i, it) in items.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            item(self, it);
+        }
+        self.out.push_str(close);
+
+        // Wrapping the outer list first gives the inner ones more room.
+        if self.no_wrap || items.is_empty() || !(self.wrapped || self.too_wide(mark.len)) {
+            self.wrapped = wrapped;
+            return;
+        }
+
+        self.reset(mark);
+        self.out.push_str(open);
+        self.indent += 1;
+        for (i, it) in items.iter().enumerate() {
+            self.newline();
+            item(self, it);
+            if i + 1 < items.len() {
+                self.out.push(',');
+            }
+        }
+        self.indent -= 1;
+        self.newline();
+        self.out.push_str(close);
+
+        self.wrapped = true;
+    }
+
+    // Whether anything printed since `from` goes past the line width.
+    fn too_wide(&self, from: usize) -> bool {
+        let line_start = self.out[..from].rfind('\n').map_or(0, |i| i + 1);
+        self.out[line_start..]
+            .lines()
+            .any(|line| line.chars().count() > WIDTH)
+    }
+
+    fn mark(&self) -> Mark {
+        Mark {
+            len: self.out.len(),
+            span: self.span,
+            block_end: self.block_end,
+            comment: self.comment,
+            literal: self.literal,
+            interpolation: self.interpolation,
+            last_line: self.last_line,
+            fresh: self.fresh,
+        }
+    }
+
+    fn reset(&mut self, mark: Mark) {
+        self.out.truncate(mark.len);
+        self.span = mark.span;
+        self.block_end = mark.block_end;
+        self.comment = mark.comment;
+        self.literal = mark.literal;
+        self.interpolation = mark.interpolation;
+        self.last_line = mark.last_line;
+        self.fresh = mark.fresh;
+    }
+
+    fn params(&mut self, params: &[String]) {
+        self.out.push('(');
+        self.out.push_str(&params.join(", "));
+        self.out.push(')');
+    }
+
+    fn string(&mut self, segments: &[&Expr]) {
+        if let Some(Expr::Literal(Lit::Str(_))) = segments.first() {
+            self.inline_comments();
+        }
+        self.out.push('"');
+
+        for segment in segments {
+            match segment {
+                Expr::Literal(Lit::Str(_)) => self.next_literal(),
+                Expr::Unary(UnOp::Str, expr, _) => {
+                    let no_wrap = mem::replace(&mut self.no_wrap, true);
+                    self.out.push_str("${");
+                    self.visit_expr(expr);
+                    self.out.push('}');
+                    self.no_wrap = no_wrap;
+                }
+                _ => unreachable!("not a string segment"),
+            }
+        }
+
+        self.out.push('"');
+    }
+}
+
+// The parser turns `"a ${b} c"` into `"a " + str(b) + " c"`. Returns the
+// pieces of a chain that could have come from such a string. Whether it did
+// is up to the layout, since `"a " + "${b}" + " c"` gives the same tree.
+fn interpolation(expr: &Expr) -> Option<Vec<&Expr>> {
+    fn is_segment(expr: &Expr) -> bool {
+        matches!(
+            expr,
+            Expr::Literal(Lit::Str(_)) | Expr::Unary(UnOp::Str, ..)
+        )
+    }
+
+    let mut segments = vec![];
+    let mut expr = expr;
+
+    loop {
+        match expr {
+            Expr::Binary {
+                lhs,
+                op: BinOp::Add,
+                rhs,
+                ..
+            } if is_segment(rhs) => {
+                segments.push(rhs.as_ref());
+                expr = lhs;
+            }
+            _ if is_segment(expr) => {
+                segments.push(expr);
+                break;
+            }
+            _ => return None,
+        }
+    }
+
+    // A chain of plain literals is just string concatenation.
+    if !segments
+        .iter()
+        .any(|e| matches!(e, Expr::Unary(UnOp::Str, ..)))
+    {
+        return None;
+    }
+
+    segments.reverse();
+    Some(segments)
+}
+
+fn escape(out: &mut String, s: &str) {
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            '$' if chars.peek() == Some(&'{') => out.push_str("\\$"),
+            c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => out.push(c),
+        }
+    }
+}
+
+impl ExprVisitor for Formatter {
+    type Output = ();
+
+    // Handles what the default can't tell apart: arrow functions, and
+    // interpolated strings, which the parser desugars.
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Some(segments) = interpolation(expr) {
+            // The chain is the next interpolated string only if it has
+            // exactly its pieces; one with more joins it to other strings.
+            if self.layout.interpolations.get(self.interpolation) == Some(&segments.len()) {
+                self.interpolation += 1;
+                return self.string(&segments);
+            }
+        }
+
+        match expr {
+            Expr::Assign(name, expr, line) => self.visit_assign_expr(name, expr, *line),
+            Expr::Binary { lhs, op, rhs, line } => self.visit_binary_expr(lhs, *op, rhs, *line),
+            Expr::Call { callee, args, line } => self.visit_call_expr(callee, args, *line),
+            Expr::Get { object, name, line } => self.visit_get_expr(object, name, *line),
+            Expr::Grouping(expr) => self.visit_grouping_expr(expr),
+            Expr::Index {
+                object,
+                index,
+                line,
+            } => self.visit_index_expr(object, index, *line),
+            Expr::Lambda {
+                params,
+                body,
+                arrow: true,
+                ..
+            } => {
+                self.params(params);
+                self.out.push_str(" => ");
+                match body.as_slice() {
+                    [Stmt::Return(Some(expr), _)] => self.visit_expr(expr),
+                    _ => unreachable!("arrow function without a body expression"),
+                }
+            }
+            Expr::Lambda {
+                params, body, line, ..
+            } => self.visit_lambda_expr(params, body, *line),
+            Expr::List(elements) => self.visit_list_expr(elements),
+            Expr::Map(entries, line) => self.visit_map_expr(entries, *line),
+            Expr::Literal(lit) => self.visit_literal_expr(lit),
+            Expr::Set {
+                object,
+                name,
+                value,
+                line,
+            } => self.visit_set_expr(object, name, value, *line),
+            Expr::SetIndex {
+                object,
+                index,
+                value,
+                line,
+            } => self.visit_set_index_expr(object, index, value, *line),
+            Expr::Logical { lhs, kw, rhs, line } => self.visit_logical_expr(lhs, *kw, rhs, *line),
+            Expr::Unary(op, expr, line) => self.visit_unary_expr(*op, expr, *line),
+            Expr::Variable(name, line) => self.visit_variable_expr(name, *line),
+        }
+    }
+
+    fn visit_assign_expr(&mut self, name: &str, expr: &Expr, _: u64) {
+        self.out.push_str(name);
+        self.out.push_str(" = ");
+        self.visit_expr(expr);
+    }
+
+    fn visit_binary_expr(&mut self, lhs: &Expr, op: BinOp, rhs: &Expr, _: u64) {
+        self.visit_expr(lhs);
+        self.out.push_str(&format!(" {} ", op));
+        self.visit_expr(rhs);
+    }
+
+    fn visit_call_expr(&mut self, callee: &Expr, args: &[Expr], _: u64) {
+        self.visit_expr(callee);
+        self.items("(", args, ")", |f, arg| f.visit_expr(arg));
+    }
+
+    fn visit_get_expr(&mut self, object: &Expr, name: &str, _: u64) {
+        self.visit_expr(object);
+        self.out.push('.');
+        self.out.push_str(name);
+    }
+
+    fn visit_grouping_expr(&mut self, expr: &Expr) {
+        self.out.push('(');
+        self.visit_expr(expr);
+        self.out.push(')');
+    }
+
+    fn visit_index_expr(&mut self, object: &Expr, index: &Expr, _: u64) {
+        self.visit_expr(object);
+        self.out.push('[');
+        self.visit_expr(index);
+        self.out.push(']');
+    }
+
+    fn visit_lambda_expr(&mut self, params: &[String], body: &[Stmt], _: u64) {
+        self.out.push_str("fun ");
+        self.params(params);
+        self.out.push(' ');
+        self.block(body);
+    }
+
+    fn visit_list_expr(&mut self, elements: &[Expr]) {
+        self.items("[", elements, "]", |f, element| f.visit_expr(element));
+    }
+
+    fn visit_map_expr(&mut self, entries: &[(Expr, Expr)], _: u64) {
+        if entries.is_empty() {
+            return self.out.push_str("{:}");
+        }
+
+        self.items("{", entries, "}", |f, (key, value)| {
+            f.visit_expr(key);
+            f.out.push_str(": ");
+            f.visit_expr(value);
+        });
+    }
+
+    fn visit_literal_expr(&mut self, literal: &Lit) {
+        match literal {
+            Lit::Str(_) => {
+                self.inline_comments();
+                self.out.push('"');
+                self.next_literal();
+                self.out.push('"');
+            }
+            Lit::Num(_) => {
+                self.inline_comments();
+                self.next_literal();
+            }
+            Lit::Bool(b) => self.out.push_str(&b.to_string()),
+            Lit::Nil => self.out.push_str("nil"),
+        }
+    }
+
+    fn visit_logical_expr(&mut self, lhs: &Expr, kw: Keyword, rhs: &Expr, _: u64) {
+        self.visit_expr(lhs);
+        self.out.push_str(&format!(" {} ", kw));
+        self.visit_expr(rhs);
+    }
+
+    fn visit_set_expr(&mut self, object: &Expr, name: &str, value: &Expr, _: u64) {
+        self.visit_expr(object);
+        self.out.push('.');
+        self.out.push_str(name);
+        self.out.push_str(" = ");
+        self.visit_expr(value);
+    }
+
+    fn visit_set_index_expr(&mut self, object: &Expr, index: &Expr, value: &Expr, _: u64) {
+        self.visit_index_expr(object, index, 0);
+        self.out.push_str(" = ");
+        self.visit_expr(value);
+    }
+
+    fn visit_unary_expr(&mut self, op: UnOp, rhs: &Expr, _: u64) {
+        match op {
+            UnOp::Neg => self.out.push('-'),
+            UnOp::Not => self.out.push('!'),
+            UnOp::Str => unreachable!("conversions are printed as strings"),
+        }
+        self.visit_expr(rhs);
+    }
+
+    fn visit_variable_expr(&mut self, name: &str, _: u64) {
+        self.out.push_str(name);
+    }
+}
+
+impl StmtVisitor for Formatter {
+    type Output = ();
+
+    fn visit_block_stmt(&mut self, stmts: &[Stmt]) {
+        self.block(stmts);
+    }
+
+    fn visit_break_stmt(&mut self, _: u64) {
+        self.out.push_str("break;");
+    }
+
+    fn visit_continue_stmt(&mut self, _: u64) {
+        self.out.push_str("continue;");
+    }
+
+    fn visit_expression_stmt(&mut self, expr: &Expr) {
+        self.visit_expr(expr);
+        self.out.push(';');
+    }
+
+    fn visit_for_stmt(
+        &mut self,
+        initializer: Option<&Stmt>,
+        condition: Option<&Expr>,
+        increment: Option<&Expr>,
+        body: &Stmt,
+    ) {
+        self.out.push_str("for (");
+        match initializer {
+            Some(initializer) => {
+                self.next_span();
+                self.visit_stmt(initializer);
+            }
+            None => self.out.push(';'),
+        }
+        if let Some(condition) = condition {
+            self.out.push(' ');
+            self.visit_expr(condition);
+        }
+        self.out.push(';');
+        if let Some(increment) = increment {
+            self.out.push(' ');
+            self.visit_expr(increment);
+        }
+        self.out.push(')');
+        self.body(body);
+    }
+
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &str,
+        iterable: &Expr,
+        range_end: Option<&Expr>,
+        body: &Stmt,
+        _: u64,
+    ) {
+        self.out.push_str(&format!("for ({} in ", name));
+        self.visit_expr(iterable);
+        if let Some(range_end) = range_end {
+            self.out.push_str("..");
+            self.visit_expr(range_end);
+        }
+        self.out.push(')');
+        self.body(body);
+    }
+
+    fn visit_function_stmt(&mut self, name: &str, params: &[String], body: &[Stmt], _: u64) {
+        self.out.push_str("fun ");
+        self.out.push_str(name);
+        self.params(params);
+        self.out.push(' ');
+        self.block(body);
+    }
+
+    fn visit_if_stmt(&mut self, condition: &Expr, then: &Stmt, r#else: Option<&Stmt>) {
+        self.out.push_str("if (");
+        self.visit_expr(condition);
+        self.out.push(')');
+        self.body(then);
+
+        if let Some(r#else) = r#else {
+            match then {
+                Stmt::Block(_) => self.out.push(' '),
+                _ => self.newline(),
+            }
+            self.out.push_str("else");
+            self.body(r#else);
+        }
+    }
+
+    fn visit_import_stmt(&mut self, path: &str, alias: Option<&str>, names: &[String], _: u64) {
+        let mut quoted = String::new();
+        escape(&mut quoted, path);
+
+        match alias {
+            Some(alias) => self
+                .out
+                .push_str(&format!("import \"{}\" as {};", quoted, alias)),
+            None => self
+                .out
+                .push_str(&format!("from \"{}\" import {};", quoted, names.join(", "))),
+        }
+    }
+
+    fn visit_print_stmt(&mut self, expr: &Expr) {
+        self.out.push_str("print ");
+        self.visit_expr(expr);
+        self.out.push(';');
+    }
+
+    fn visit_return_stmt(&mut self, expr: Option<&Expr>, _: u64) {
+        self.out.push_str("return");
+        if let Some(expr) = expr {
+            self.out.push(' ');
+            self.visit_expr(expr);
+        }
+        self.out.push(';');
+    }
+
+    fn visit_throw_stmt(&mut self, expr: &Expr, _: u64) {
+        self.out.push_str("throw ");
+        self.visit_expr(expr);
+        self.out.push(';');
+    }
+
+    fn visit_try_stmt(
+        &mut self,
+        body: &[Stmt],
+        catch: Option<(&str, &[Stmt])>,
+        finally: Option<&[Stmt]>,
+    ) {
+        self.out.push_str("try ");
+        self.block(body);
+
+        if let Some((name, handler)) = catch {
+            self.out.push_str(&format!(" catch ({}) ", name));
+            self.block(handler);
+        }
+
+        if let Some(finally) = finally {
+            self.out.push_str(" finally ");
+            self.block(finally);
+        }
+    }
+
+    fn visit_var_stmt(&mut self, name: &str, initializer: Option<&Expr>) {
+        self.out.push_str("var ");
+        self.out.push_str(name);
+        if let Some(initializer) = initializer {
+            self.out.push_str(" = ");
+            self.visit_expr(initializer);
+        }
+        self.out.push(';');
+    }
+
+    fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) {
+        self.out.push_str("while (");
+        self.visit_expr(condition);
+        self.out.push(')');
+        self.body(body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn literals_keep_their_spelling() {
+        let src = "print 0x1F + 0b1010 + 1_000 + 1e3 + 1.50;\nprint \"\\u{1F600} ${1e-300}\\t\";\n";
+        assert_eq!(crate::format(src).unwrap(), src);
+    }
+
+    #[test]
+    fn block_comments_stay_in_place() {
+        let src = "var x = /* c */ 1;\n/* lead */ print \"a ${/* n */ 2}\";\n";
+        assert_eq!(crate::format(src).unwrap(), src);
+
+        assert_eq!(
+            crate::format("{ /* inline */ print 1; }").unwrap(),
+            "{ /* inline */\n  print 1;\n}\n"
+        );
+    }
+
+    #[test]
+    fn only_interpolated_strings_are_joined() {
+        let src =
+            "print \"a\" + \"${1}\" + \"b\";\nprint \"${1}\" + \"${2}\" + \"x ${\"y ${3}\"}\";\n";
+        assert_eq!(crate::format(src).unwrap(), src);
+    }
+}
@@ -10,7 +10,8 @@ use crate::{Interpreter, LoxError};
 
 #[derive(Debug, Clone)]
 pub struct Func {
-    name: String,
+    // `None` for lambdas
+    name: Option<String>,
     params: Vec<String>,
     body: Vec<Stmt>,
     closure: Rc<RefCell<Env>>,
@@ -19,7 +20,16 @@ pub struct Func {
 impl Func {
     pub fn new(name: &str, params: &[String], body: &[Stmt], closure: Rc<RefCell<Env>>) -> Self {
         Func {
-            name: name.into(),
+            name: Some(name.into()),
+            params: params.to_vec(),
+            body: body.to_vec(),
+            closure,
+        }
+    }
+
+    pub fn anonymous(params: &[String], body: &[Stmt], closure: Rc<RefCell<Env>>) -> Self {
+        Func {
+            name: None,
             params: params.to_vec(),
             body: body.to_vec(),
             closure,
@@ -42,16 +52,19 @@ impl Callable for Func {
 
         let res = interpreter.execute_block(&self.body, Rc::new(RefCell::new(env)));
 
-        if let Err(LoxError::Return(val)) = res {
-            Ok(val)
-        } else {
-            Ok(Value::Nil)
+        match res {
+            Ok(()) => Ok(Value::Nil),
+            Err(LoxError::Return(val)) => Ok(val),
+            Err(e) => Err(e),
         }
     }
 }
 
 impl fmt::Display for Func {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "<fn {}>", self.name)
+        match &self.name {
+            Some(name) => write!(f, "<fn {}>", name),
+            None => f.write_str("<fn>"),
+        }
     }
 }
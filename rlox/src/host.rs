@@ -0,0 +1,96 @@
+use std::any::Any;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::limits::HeapSize;
+use crate::native::Args;
+use crate::value::{Callable, Value};
+use crate::{Interpreter, LoxError};
+
+/// A Rust value handed to scripts, which treat it like an instance:
+/// `obj.field` calls `get`, `obj.field = x` calls `set` and `obj.method(x)`
+/// calls `call_method`. It prints using its `Display` impl.
+///
+/// Host objects are shared behind an `Rc`, so mutable state needs interior
+/// mutability.
+pub trait HostObject: Any + fmt::Debug + fmt::Display {
+    /// What `type()` reports for this object.
+    fn type_name(&self) -> &'static str {
+        "object"
+    }
+
+    /// Returns the value of field `name`, if there is one.
+    fn get(&self, _name: &str) -> Option<Value> {
+        None
+    }
+
+    /// Assigns to field `name`.
+    fn set(&self, _name: &str, _value: Value) -> Result<(), LoxError> {
+        Err(LoxError::native("Only instances have fields."))
+    }
+
+    /// Returns the arity of method `name`, if there is one. Fields shadow
+    /// methods of the same name.
+    fn method_arity(&self, _name: &str) -> Option<usize> {
+        None
+    }
+
+    /// Calls method `name`. Only called for names `method_arity` accepts,
+    /// with that many arguments.
+    fn call_method(&self, name: &str, _args: Args) -> Result<Value, LoxError> {
+        Err(LoxError::native(format!("Undefined property '{}'.", name)))
+    }
+
+    /// Whether `==` holds against another host object. By default, objects
+    /// are only equal to themselves.
+    fn equals(&self, _other: &dyn HostObject) -> bool {
+        false
+    }
+}
+
+impl dyn HostObject {
+    pub fn downcast_ref<T: HostObject>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref()
+    }
+}
+
+pub fn get(object: &Rc<dyn HostObject>, name: &str) -> Option<Value> {
+    if let Some(value) = object.get(name) {
+        return Some(value);
+    }
+
+    let arity = object.method_arity(name)?;
+    let method = HostMethod {
+        receiver: object.clone(),
+        name: name.into(),
+        arity,
+    };
+    Some(Value::Call(Rc::new(method)))
+}
+
+/// A host object method bound to its receiver.
+#[derive(Debug)]
+struct HostMethod {
+    receiver: Rc<dyn HostObject>,
+    name: String,
+    arity: usize,
+}
+
+impl Callable for HostMethod {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, LoxError> {
+        let args = Args::new(args, interpreter.heap_left());
+        let value = self.receiver.call_method(&self.name, args)?;
+        interpreter.charge(value.heap_size())?;
+        Ok(value)
+    }
+}
+
+impl fmt::Display for HostMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<native fn>")
+    }
+}
@@ -1,12 +1,26 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::Write;
 use std::mem;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use crate::ast::{BinOp, Expr, Keyword, Lit, Stmt, UnOp};
 use crate::clock::Clock;
 use crate::env::Env;
 use crate::function::Func;
+use crate::host;
+use crate::io;
+use crate::limits::{self, InterruptHandle, Limits, LIST_SLOT, MAP_ENTRY};
+use crate::list;
+use crate::map::{self, Key, Map};
+use crate::math;
+use crate::module::Module;
+use crate::native::{Args, NativeFn};
+use crate::platform::{Host, SystemHost};
+use crate::resolver::Resolver;
+use crate::string;
 use crate::value::Value;
 use crate::visitor::{ExprVisitor, StmtVisitor};
 use crate::LoxError;
@@ -14,32 +28,244 @@ use crate::LoxError;
 pub struct Interpreter {
     env: Rc<RefCell<Env>>,
     globals: Rc<RefCell<Env>>,
+    // Natives, shared by the main program and every module it imports. Each
+    // of those gets its own globals enclosed by this environment.
+    builtins: Rc<RefCell<Env>>,
     locals: HashMap<String, usize>,
+    // Directories searched for imports not found next to the importing file
+    search_paths: Vec<PathBuf>,
+    // Whether imports may read any file, rather than only ones under the
+    // main script's directory or a search path
+    io_allowed: bool,
+    // Loaded modules, by canonical path
+    modules: HashMap<PathBuf, Rc<Module>>,
+    // Modules currently being loaded, innermost last
+    loading: Vec<PathBuf>,
+    // Where `print` writes to
+    out: Box<dyn Write>,
+    limits: Limits,
+    // Work done so far in the current run, checked against `limits`
+    steps: u64,
+    depth: usize,
+    heap: usize,
+    // The line of the statement being run, for limit errors
+    line: u64,
+    interrupt: InterruptHandle,
+    // Source of time and randomness for the natives
+    host: Box<dyn Host>,
 }
 
 impl Interpreter {
     pub fn new() -> Interpreter {
-        let mut env = Env::new();
-        env.define("clock", Value::Call(Rc::new(Clock)));
-        let globals = Rc::new(RefCell::new(env));
+        let mut builtins = Env::new();
+        builtins.define("clock", Value::Call(Rc::new(Clock)));
+        math::define(&mut builtins);
+        string::define(&mut builtins);
+        let builtins = Rc::new(RefCell::new(builtins));
+
+        let globals = Rc::new(RefCell::new(Env::with_environment(builtins.clone())));
 
         Interpreter {
             env: globals.clone(),
             globals,
+            builtins,
             locals: HashMap::new(),
+            search_paths: vec![],
+            io_allowed: false,
+            modules: HashMap::new(),
+            loading: vec![],
+            out: Box::new(std::io::stdout()),
+            limits: Limits::default(),
+            steps: 0,
+            depth: 0,
+            heap: 0,
+            line: 0,
+            interrupt: InterruptHandle::default(),
+            host: Box::new(SystemHost::new()),
         }
     }
 
-    pub fn globals(&self) -> Rc<RefCell<Env>> {
-        self.globals.clone()
+    /// Replaces where `clock()` and `random()` get their values from. Use a
+    /// `DeterministicHost` for reproducible runs.
+    pub fn set_host(&mut self, host: impl Host + 'static) {
+        self.host = Box::new(host);
+    }
+
+    pub(crate) fn host(&mut self) -> &mut dyn Host {
+        self.host.as_mut()
+    }
+
+    /// Caps the number of statements a run may execute. `None`, the default,
+    /// means no limit.
+    pub fn set_step_limit(&mut self, steps: Option<u64>) {
+        self.limits.steps = steps;
+    }
+
+    /// Sets how deeply calls may nest before failing with "Stack overflow.".
+    /// Each Lox call uses several kilobytes of native stack, so raising this
+    /// far above the default may need a bigger thread stack.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.limits.call_depth = depth;
+    }
+
+    /// Caps the bytes a run may allocate for strings, lists and maps. Memory
+    /// is never given back, so this bounds total allocation rather than live
+    /// memory, and it is an estimate. `None`, the default, means no limit.
+    pub fn set_heap_limit(&mut self, bytes: Option<usize>) {
+        self.limits.heap = bytes;
+    }
+
+    /// A handle other threads can use to stop the running program.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    // How many more bytes the current run may allocate, if it is limited.
+    pub(crate) fn heap_left(&self) -> Option<usize> {
+        self.limits.heap.map(|max| max.saturating_sub(self.heap))
+    }
+
+    // Counts `bytes` of allocation against the heap limit. The interpreter
+    // does this before allocating, while natives are charged for what they
+    // return.
+    pub(crate) fn charge(&mut self, bytes: usize) -> Result<(), LoxError> {
+        self.heap += bytes;
+        match self.limits.heap {
+            Some(max) if self.heap > max => Err(LoxError::Limit("Out of memory.".into(), 0)),
+            _ => Ok(()),
+        }
+    }
+
+    fn charge_at(&mut self, bytes: usize, line: u64) -> Result<(), LoxError> {
+        self.charge(bytes).map_err(|e| match e {
+            LoxError::Limit(msg, 0) => LoxError::Limit(msg, line),
+            e => e,
+        })
+    }
+
+    fn start_run(&mut self) {
+        self.steps = 0;
+        self.depth = 0;
+        self.heap = 0;
+    }
+
+    // An interrupt sent before a run stops it, but one that comes in after
+    // the last statement shouldn't carry over to the next run.
+    fn finish_run<T>(&mut self, res: Result<T, LoxError>) -> Result<T, LoxError> {
+        self.interrupt.clear();
+        res
+    }
+
+    /// Sends the output of `print` statements to `out` instead of stdout.
+    pub fn set_output(&mut self, out: impl Write + 'static) {
+        self.out = Box::new(out);
+    }
+
+    pub fn add_search_path(&mut self, dir: impl Into<PathBuf>) {
+        self.search_paths.push(dir.into());
+    }
+
+    /// Enables the file and process natives (`read_file`, `write_file`,
+    /// `read_line`, `args`, `env` and `exit`). They are left out by default
+    /// so that embedded scripts cannot touch the host. `args` is what the
+    /// script sees from `args()`.
+    ///
+    /// It also lets `import` read any file, including ones relative to the
+    /// current directory. Otherwise modules must be under the main
+    /// script's directory or a search path.
+    pub fn allow_io(&mut self, args: Vec<String>) {
+        io::define(&mut self.builtins.borrow_mut(), args);
+        self.io_allowed = true;
+    }
+
+    /// Records the file the main program was read from, so that its imports
+    /// are resolved relative to it and importing it back is a cycle.
+    pub fn set_script_path(&mut self, path: impl AsRef<Path>) {
+        if let Ok(path) = path.as_ref().canonicalize() {
+            self.loading = vec![path];
+        }
+    }
+
+    /// Registers a Rust closure as a global function taking exactly `arity`
+    /// arguments. Like the other builtins, it is visible from every module.
+    pub fn register_fn(
+        &mut self,
+        name: &str,
+        arity: usize,
+        fun: impl Fn(Args) -> Result<Value, LoxError> + 'static,
+    ) {
+        self.define_global(name, Value::Call(Rc::new(NativeFn::new(name, arity, fun))));
+    }
+
+    /// Like `register_fn`, but the function accepts `min_arity` or more
+    /// arguments.
+    pub fn register_variadic_fn(
+        &mut self,
+        name: &str,
+        min_arity: usize,
+        fun: impl Fn(Args) -> Result<Value, LoxError> + 'static,
+    ) {
+        let native = NativeFn::variadic(name, min_arity, fun);
+        self.define_global(name, Value::Call(Rc::new(native)));
+    }
+
+    /// Looks up a global defined by the program, or a builtin.
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.globals.borrow_mut().get(name).ok()
+    }
+
+    /// Calls a function value from Rust, such as one fetched with
+    /// `get_global`, checking its arity first.
+    pub fn call(&mut self, callee: &Value, args: &[Value]) -> Result<Value, LoxError> {
+        self.start_run();
+        let res = self.call_value(callee.clone(), args.to_vec(), 0);
+        self.finish_run(res)
+    }
+
+    /// Defines `name` alongside the builtins, so every module can see it.
+    pub fn define_global(&mut self, name: &str, value: Value) {
+        self.builtins.borrow_mut().define(name, value);
     }
 
     pub fn interpret(&mut self, stmts: Vec<Stmt>) -> Result<(), LoxError> {
-        for stmt in stmts {
-            self.execute(&stmt)?
+        self.eval(stmts).map(|_| ())
+    }
+
+    /// Runs `stmts` like `interpret`. If the last one is an expression
+    /// statement, its value is returned instead of being discarded, which
+    /// is how the REPL echoes results.
+    pub fn eval(&mut self, stmts: Vec<Stmt>) -> Result<Option<Value>, LoxError> {
+        Resolver::new(self).resolve(&stmts)?;
+        self.start_run();
+        let res = self.run_eval(stmts);
+        self.finish_run(res)
+    }
+
+    fn run_eval(&mut self, mut stmts: Vec<Stmt>) -> Result<Option<Value>, LoxError> {
+        let last = match stmts.last() {
+            Some(Stmt::Expr(_)) => stmts.pop(),
+            _ => None,
+        };
+
+        for stmt in &stmts {
+            self.execute(stmt)?
         }
 
-        Ok(())
+        match &last {
+            Some(stmt @ Stmt::Expr(expr)) => {
+                self.step(stmt)?;
+                self.evaluate(expr).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // Resolves and runs a module's statements as part of the current run,
+    // so its steps, call depth and allocations count against the importer's
+    // limits and a pending interrupt still stops it.
+    fn run_stmts(&mut self, stmts: &[Stmt]) -> Result<(), LoxError> {
+        Resolver::new(self).resolve(stmts)?;
+        stmts.iter().try_for_each(|stmt| self.execute(stmt))
     }
 
     pub fn resolve(&mut self, name: &str, depth: usize) {
@@ -64,9 +290,211 @@ impl Interpreter {
     }
 
     fn execute(&mut self, stmt: &Stmt) -> Result<(), LoxError> {
+        self.step(stmt)?;
         self.visit_stmt(stmt)
     }
 
+    // Accounts for `stmt` against the step limit, and checks whether the
+    // program has been interrupted. A statement without a line of its own,
+    // like `print 1;`, is taken to be where the last one was.
+    fn step(&mut self, stmt: &Stmt) -> Result<(), LoxError> {
+        if let Some(line) = stmt.line() {
+            self.line = line;
+        }
+
+        self.steps += 1;
+        if self.limits.steps.is_some_and(|max| self.steps > max) {
+            return Err(LoxError::Limit("Step limit exceeded.".into(), self.line));
+        }
+        if self.interrupt.take() {
+            return Err(LoxError::Limit("Interrupted.".into(), self.line));
+        }
+
+        Ok(())
+    }
+
+    fn call_value(
+        &mut self,
+        callee: Value,
+        args: Vec<Value>,
+        line: u64,
+    ) -> Result<Value, LoxError> {
+        let fun = match callee {
+            Value::Call(fun) => fun,
+            _ => {
+                let msg = "Can only call functions and classes.";
+                return Err(LoxError::Runtime(msg.into(), line));
+            }
+        };
+
+        if fun.variadic() && args.len() < fun.arity() {
+            let msg = format!(
+                "Expected at least {} arguments but got {}.",
+                fun.arity(),
+                args.len()
+            );
+            return Err(LoxError::Runtime(msg, line));
+        }
+
+        if !fun.variadic() && args.len() != fun.arity() {
+            let msg = format!("Expected {} arguments but got {}.", fun.arity(), args.len());
+            return Err(LoxError::Runtime(msg, line));
+        }
+
+        if self.depth >= self.limits.call_depth {
+            return Err(LoxError::Runtime("Stack overflow.".into(), line));
+        }
+
+        self.depth += 1;
+        let res = fun.call(self, args);
+        self.depth -= 1;
+
+        match res {
+            Err(LoxError::Runtime(msg, 0)) => Err(LoxError::Runtime(msg, line)),
+            Err(LoxError::Limit(msg, 0)) => Err(LoxError::Limit(msg, line)),
+            res => res,
+        }
+    }
+
+    fn get_property(&self, object: &Value, name: &str, line: u64) -> Result<Value, LoxError> {
+        let method = match object {
+            Value::List(list) => list::method(list, name),
+            Value::Map(map) => map::method(map, name),
+            Value::Module(module) => module.get(name),
+            Value::Host(object) => host::get(object, name),
+            _ => {
+                let msg = "Only instances have properties.";
+                return Err(LoxError::Runtime(msg.into(), line));
+            }
+        };
+
+        method.ok_or_else(|| LoxError::Runtime(format!("Undefined property '{}'.", name), line))
+    }
+
+    // Runs one pass of a loop body, returning whether the loop should go on.
+    fn loop_body(&mut self, body: &Stmt) -> Result<bool, LoxError> {
+        match self.execute(body) {
+            Ok(()) | Err(LoxError::Continue) => Ok(true),
+            Err(LoxError::Break) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn for_loop(
+        &mut self,
+        initializer: Option<&Stmt>,
+        condition: Option<&Expr>,
+        increment: Option<&Expr>,
+        body: &Stmt,
+    ) -> Result<(), LoxError> {
+        if let Some(stmt) = initializer {
+            self.execute(stmt)?;
+        }
+
+        while match condition {
+            Some(expr) => self.evaluate(expr)?.is_truthy(),
+            None => true,
+        } {
+            if !self.loop_body(body)? {
+                break;
+            }
+
+            if let Some(expr) = increment {
+                self.evaluate(expr)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Binds the loop variable in a fresh environment for each iteration, so
+    // closures created in the body capture that iteration's value.
+    fn for_in_body(&mut self, name: &str, value: Value, body: &Stmt) -> Result<bool, LoxError> {
+        let mut env = Env::with_environment(self.env.clone());
+        env.define(name, value);
+
+        let prev = mem::replace(&mut self.env, Rc::new(RefCell::new(env)));
+        let res = self.loop_body(body);
+        self.env = prev;
+
+        res
+    }
+
+    fn catch(&mut self, name: &str, handler: &[Stmt], error: Value) -> Result<(), LoxError> {
+        let mut env = Env::with_environment(self.env.clone());
+        env.define(name, error);
+        self.execute_block(handler, Rc::new(RefCell::new(env)))
+    }
+
+    fn import(&mut self, path: &str, line: u64) -> Result<Rc<Module>, LoxError> {
+        let runtime_error = |msg| LoxError::Runtime(msg, line);
+
+        let file = self
+            .find_module(path)
+            .ok_or_else(|| runtime_error(format!("Can't find module '{}'.", path)))?;
+
+        if let Some(module) = self.modules.get(&file) {
+            return Ok(module.clone());
+        }
+
+        if let Some(start) = self.loading.iter().position(|p| *p == file) {
+            let cycle = self.loading[start..]
+                .iter()
+                .chain(Some(&file))
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(runtime_error(format!("Import cycle detected: {}.", cycle)));
+        }
+
+        let src = std::fs::read_to_string(&file)
+            .map_err(|e| runtime_error(format!("Can't read module '{}': {}.", path, e)))?;
+        let stmts = crate::parse(&src).map_err(|e| {
+            // One line, so the import's own `[line N]` still comes next.
+            let errors = e.to_string().replace('\n', " ");
+            runtime_error(format!("Can't parse module '{}': {}", path, errors))
+        })?;
+
+        let env = Rc::new(RefCell::new(Env::with_environment(self.builtins.clone())));
+        let prev = mem::replace(&mut self.env, env.clone());
+        self.loading.push(file.clone());
+
+        let res = self.run_stmts(&stmts);
+
+        self.loading.pop();
+        self.env = prev;
+        res?;
+
+        let name = file.file_stem().unwrap_or_default().to_string_lossy();
+        let module = Rc::new(Module::new(&name, env));
+        self.modules.insert(file, module.clone());
+
+        Ok(module)
+    }
+
+    // Looks next to the importing module first, then in each search path.
+    // Without I/O access, files outside those are treated as missing.
+    fn find_module(&self, path: &str) -> Option<PathBuf> {
+        let importer_dir = self.loading.last().and_then(|p| p.parent());
+        let script_dir = self.loading.first().and_then(|p| p.parent());
+        let cwd = Some(Path::new("")).filter(|_| self.io_allowed);
+
+        let roots = script_dir
+            .into_iter()
+            .chain(self.search_paths.iter().map(PathBuf::as_path))
+            .filter_map(|dir| dir.canonicalize().ok())
+            .collect::<Vec<_>>();
+
+        importer_dir
+            .into_iter()
+            .chain(self.search_paths.iter().map(PathBuf::as_path))
+            .chain(cwd)
+            .map(|dir| dir.join(path))
+            .filter(|p| p.is_file())
+            .filter_map(|p| p.canonicalize().ok())
+            .find(|p| self.io_allowed || roots.iter().any(|root| p.starts_with(root)))
+    }
+
     #[allow(unused)]
     fn lookup_variable(&mut self, name: &str, line: u64) -> Result<Value, LoxError> {
         let res = if let Some(n) = self.locals.get(name) {
@@ -79,6 +507,9 @@ impl Interpreter {
     }
 }
 
+const NOT_INDEXABLE: &str = "Can only index lists and maps.";
+const NOT_ITERABLE: &str = "Can only iterate over lists, maps, strings, ranges and iterators.";
+
 impl ExprVisitor for Interpreter {
     type Output = Result<Value, LoxError>;
 
@@ -102,6 +533,11 @@ impl ExprVisitor for Interpreter {
         let lhs = self.evaluate(lhs)?;
         let rhs = self.evaluate(rhs)?;
 
+        // Only joining strings allocates.
+        if let (BinOp::Add, Value::Str(a), Value::Str(b)) = (op, &lhs, &rhs) {
+            self.charge_at(a.len() + b.len(), line)?;
+        }
+
         let res = match op {
             BinOp::Sub => lhs.sub(&rhs),
             BinOp::Div => lhs.div(&rhs),
@@ -124,26 +560,62 @@ impl ExprVisitor for Interpreter {
         args: &[Expr],
         line: u64,
     ) -> Result<Value, LoxError> {
-        if let Value::Call(fun) = self.evaluate(callee)? {
-            let mut values = vec![];
-            for arg in args {
-                values.push(self.evaluate(arg)?)
-            }
+        let callee = self.evaluate(callee)?;
 
-            if values.len() != fun.arity() {
-                let msg = format!(
-                    "Expected {} arguments but got {}.",
-                    fun.arity(),
-                    values.len()
-                );
-                return Err(LoxError::Runtime(msg, line));
-            }
+        let mut values = vec![];
+        for arg in args {
+            values.push(self.evaluate(arg)?)
+        }
 
-            fun.call(self, values)
-        } else {
-            let msg = "Can only call functions and classes.";
-            Err(LoxError::Runtime(msg.into(), line))
+        self.call_value(callee, values, line)
+    }
+
+    fn visit_get_expr(&mut self, object: &Expr, name: &str, line: u64) -> Self::Output {
+        let object = self.evaluate(object)?;
+        self.get_property(&object, name, line)
+    }
+
+    fn visit_index_expr(&mut self, object: &Expr, index: &Expr, line: u64) -> Self::Output {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+
+        let res = match object {
+            Value::List(list) => list::get(&list, &index).map_err(Into::into),
+            Value::Map(map) => map::get(&map, &index),
+            _ => Err(NOT_INDEXABLE.into()),
+        };
+
+        res.map_err(|e| LoxError::Runtime(e, line))
+    }
+
+    fn visit_lambda_expr(&mut self, params: &[String], body: &[Stmt], _: u64) -> Self::Output {
+        let fun = Func::anonymous(params, body, self.env.clone());
+        Ok(Value::Call(Rc::new(fun)))
+    }
+
+    fn visit_list_expr(&mut self, elements: &[Expr]) -> Self::Output {
+        self.charge_at(elements.len() * LIST_SLOT, self.line)?;
+
+        let mut values = Vec::with_capacity(elements.len());
+        for element in elements {
+            values.push(self.evaluate(element)?);
         }
+
+        Ok(Value::List(Rc::new(RefCell::new(values))))
+    }
+
+    fn visit_map_expr(&mut self, entries: &[(Expr, Expr)], line: u64) -> Self::Output {
+        // Repeated keys make this an overestimate.
+        self.charge_at(entries.len() * MAP_ENTRY, line)?;
+        let mut map = Map::new();
+
+        for (key, value) in entries {
+            let key = self.evaluate(key)?;
+            let key = Key::try_from(&key).map_err(|e| LoxError::Runtime(e.into(), line))?;
+            map.insert(key, self.evaluate(value)?);
+        }
+
+        Ok(Value::Map(Rc::new(RefCell::new(map))))
     }
 
     fn visit_literal_expr(&mut self, literal: &Lit) -> Self::Output {
@@ -166,12 +638,67 @@ impl ExprVisitor for Interpreter {
         }
     }
 
+    fn visit_set_expr(
+        &mut self,
+        object: &Expr,
+        name: &str,
+        value: &Expr,
+        line: u64,
+    ) -> Self::Output {
+        let object = match self.evaluate(object)? {
+            Value::Host(object) => object,
+            _ => {
+                let msg = "Only instances have fields.";
+                return Err(LoxError::Runtime(msg.into(), line));
+            }
+        };
+
+        let value = self.evaluate(value)?;
+        match object.set(name, value.clone()) {
+            Ok(()) => Ok(value),
+            Err(LoxError::Runtime(msg, 0)) => Err(LoxError::Runtime(msg, line)),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn visit_set_index_expr(
+        &mut self,
+        object: &Expr,
+        index: &Expr,
+        value: &Expr,
+        line: u64,
+    ) -> Self::Output {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        let value = self.evaluate(value)?;
+
+        // Only a new map key grows the object.
+        if let Value::Map(map) = &object {
+            if !map::contains(map, &index) {
+                self.charge_at(MAP_ENTRY, line)?;
+            }
+        }
+
+        let res = match &object {
+            Value::List(list) => list::set(list, &index, value.clone()).map_err(Into::into),
+            Value::Map(map) => map::set(map, &index, value.clone()),
+            _ => Err(NOT_INDEXABLE.into()),
+        };
+        res.map_err(|e| LoxError::Runtime(e, line))?;
+
+        Ok(value)
+    }
+
     fn visit_unary_expr(&mut self, op: UnOp, rhs: &Expr, line: u64) -> Result<Value, LoxError> {
         let val = self.evaluate(rhs)?;
 
         let res = match op {
             UnOp::Neg => val.neg(),
             UnOp::Not => Ok(Value::from(!val.is_truthy())),
+            UnOp::Str => {
+                self.charge_at(limits::display_size(&val), line)?;
+                Ok(Value::Str(val.to_string()))
+            }
         };
 
         res.map_err(|e| LoxError::Runtime(e.to_string(), line))
@@ -190,15 +717,117 @@ impl StmtVisitor for Interpreter {
 
     fn visit_block_stmt(&mut self, stmts: &[Stmt]) -> Self::Output {
         self.execute_block(
-            &stmts,
+            stmts,
             Rc::new(RefCell::new(Env::with_environment(self.env.clone()))),
         )
     }
 
+    fn visit_break_stmt(&mut self, _line: u64) -> Self::Output {
+        Err(LoxError::Break)
+    }
+
+    fn visit_continue_stmt(&mut self, _line: u64) -> Self::Output {
+        Err(LoxError::Continue)
+    }
+
     fn visit_expression_stmt(&mut self, expr: &Expr) -> Self::Output {
         self.evaluate(expr).map(|_| ())
     }
 
+    fn visit_for_stmt(
+        &mut self,
+        initializer: Option<&Stmt>,
+        condition: Option<&Expr>,
+        increment: Option<&Expr>,
+        body: &Stmt,
+    ) -> Self::Output {
+        // The initializer gets a scope of its own, which the whole loop shares.
+        let env = Env::with_environment(self.env.clone());
+        let prev = mem::replace(&mut self.env, Rc::new(RefCell::new(env)));
+        let res = self.for_loop(initializer, condition, increment, body);
+        self.env = prev;
+
+        res
+    }
+
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &str,
+        iterable: &Expr,
+        range_end: Option<&Expr>,
+        body: &Stmt,
+        line: u64,
+    ) -> Self::Output {
+        let iterable = self.evaluate(iterable)?;
+
+        if let Some(end) = range_end {
+            let bounds = (iterable, self.evaluate(end)?);
+            let (mut i, end) = match bounds {
+                (Value::Num(start), Value::Num(end)) => (start, end),
+                _ => {
+                    let msg = "Range bounds must be numbers.";
+                    return Err(LoxError::Runtime(msg.into(), line));
+                }
+            };
+
+            while i < end && self.for_in_body(name, Value::Num(i), body)? {
+                i += 1.0;
+            }
+
+            return Ok(());
+        }
+
+        match iterable {
+            Value::List(list) => {
+                // Index on every pass, since the body is free to change the list.
+                let mut i = 0;
+                loop {
+                    let item = match list.borrow().get(i) {
+                        Some(item) => item.clone(),
+                        None => break,
+                    };
+                    if !self.for_in_body(name, item, body)? {
+                        break;
+                    }
+                    i += 1;
+                }
+            }
+            Value::Map(map) => {
+                let keys: Vec<Value> = map.borrow().iter().map(|(k, _)| k.into()).collect();
+                for key in keys {
+                    if !self.for_in_body(name, key, body)? {
+                        break;
+                    }
+                }
+            }
+            Value::Str(s) => {
+                for c in s.chars() {
+                    if !self.for_in_body(name, Value::Str(c.to_string()), body)? {
+                        break;
+                    }
+                }
+            }
+            object => {
+                // Anything else must follow the iterator protocol: `iter()`
+                // returns an iterator whose `next()` yields values until nil.
+                let iter = self
+                    .get_property(&object, "iter", line)
+                    .map_err(|_| LoxError::Runtime(NOT_ITERABLE.into(), line))?;
+                let iterator = self.call_value(iter, vec![], line)?;
+                let next = self.get_property(&iterator, "next", line)?;
+
+                loop {
+                    let item = self.call_value(next.clone(), vec![], line)?;
+                    if item == Value::Nil || !self.for_in_body(name, item, body)? {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn visit_function_stmt(
         &mut self,
         name: &str,
@@ -230,8 +859,34 @@ impl StmtVisitor for Interpreter {
         Ok(())
     }
 
+    fn visit_import_stmt(
+        &mut self,
+        path: &str,
+        alias: Option<&str>,
+        names: &[String],
+        line: u64,
+    ) -> Self::Output {
+        let module = self.import(path, line)?;
+
+        for name in names {
+            let value = module.get(name).ok_or_else(|| {
+                let msg = format!("Module '{}' has no member '{}'.", module.name(), name);
+                LoxError::Runtime(msg, line)
+            })?;
+            self.env.borrow_mut().define(name, value);
+        }
+
+        if let Some(alias) = alias {
+            self.env.borrow_mut().define(alias, Value::Module(module));
+        }
+
+        Ok(())
+    }
+
     fn visit_print_stmt(&mut self, expr: &Expr) -> Self::Output {
-        self.evaluate(expr).map(|val| println!("{}", val))
+        let val = self.evaluate(expr)?;
+        writeln!(self.out, "{}", val)
+            .map_err(|e| LoxError::native(format!("Could not write output: {}.", e)))
     }
 
     fn visit_return_stmt(&mut self, expr: Option<&Expr>, _line: u64) -> Self::Output {
@@ -243,27 +898,75 @@ impl StmtVisitor for Interpreter {
         Err(LoxError::Return(val))
     }
 
+    fn visit_throw_stmt(&mut self, expr: &Expr, line: u64) -> Self::Output {
+        Err(LoxError::Throw(self.evaluate(expr)?, line))
+    }
+
+    fn visit_try_stmt(
+        &mut self,
+        body: &[Stmt],
+        catch: Option<(&str, &[Stmt])>,
+        finally: Option<&[Stmt]>,
+    ) -> Self::Output {
+        // Only errors are caught; `return`, `break` and `continue` pass
+        // straight through. Hitting a limit skips `finally` too, since it
+        // could otherwise keep the program running.
+        let res = match (self.visit_block_stmt(body), catch) {
+            (Err(e @ LoxError::Limit(..)), _) => return Err(e),
+            (Err(LoxError::Runtime(msg, line)), Some((name, handler))) => {
+                self.catch(name, handler, error_value(msg, line))
+            }
+            (Err(LoxError::Throw(value, _)), Some((name, handler))) => {
+                self.catch(name, handler, value)
+            }
+            (res, _) => res,
+        };
+
+        // Anything that escapes the `finally` block, a `return` included,
+        // replaces whatever the rest of the statement produced.
+        if let Err(e @ LoxError::Limit(..)) = res {
+            return Err(e);
+        }
+
+        if let Some(stmts) = finally {
+            self.visit_block_stmt(stmts)?;
+        }
+
+        res
+    }
+
     fn visit_var_stmt(&mut self, name: &str, initializer: Option<&Expr>) -> Self::Output {
         let val = if let Some(v) = initializer {
-            self.evaluate(&v)?
+            self.evaluate(v)?
         } else {
             Value::Nil
         };
 
-        self.env.borrow_mut().define(&name, val);
+        self.env.borrow_mut().define(name, val);
 
         Ok(())
     }
 
     fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> Self::Output {
         while self.evaluate(condition)?.is_truthy() {
-            self.execute(body)?
+            if !self.loop_body(body)? {
+                break;
+            }
         }
 
         Ok(())
     }
 }
 
+// Built-in runtime errors are caught as a map of their message and line.
+fn error_value(msg: String, line: u64) -> Value {
+    let mut map = Map::new();
+    map.insert(Key::Str("message".into()), Value::Str(msg));
+    map.insert(Key::Str("line".into()), Value::Num(line as f64));
+
+    Value::Map(Rc::new(RefCell::new(map)))
+}
+
 impl Default for Interpreter {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,61 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, BufRead};
+use std::rc::Rc;
+
+use crate::env::Env;
+use crate::native::define_fn;
+use crate::value::Value;
+use crate::LoxError;
+
+/// Defines the file and process natives in `env`. `args` is what `args()`
+/// returns to the script.
+pub fn define(env: &mut Env, args: Vec<String>) {
+    define_fn(env, "read_file", 1, |args| {
+        let path = args.str(0)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            args.reserve(metadata.len() as usize)?;
+        }
+        fs::read_to_string(path)
+            .map(Value::Str)
+            .map_err(|e| LoxError::native(format!("Could not read file '{}': {}.", path, e)))
+    });
+
+    define_fn(env, "write_file", 2, |args| {
+        let path = args.str(0)?;
+        let contents = args.str(1)?;
+        fs::write(path, contents)
+            .map(|_| Value::Nil)
+            .map_err(|e| LoxError::native(format!("Could not write file '{}': {}.", path, e)))
+    });
+
+    define_fn(env, "read_line", 0, |_| {
+        let mut line = String::new();
+        match io::stdin().lock().read_line(&mut line) {
+            Ok(0) => Ok(Value::Nil),
+            Ok(_) => {
+                let len = line.trim_end_matches(['\n', '\r']).len();
+                line.truncate(len);
+                Ok(Value::Str(line))
+            }
+            Err(e) => Err(LoxError::native(format!("Could not read input: {}.", e))),
+        }
+    });
+
+    define_fn(env, "args", 0, move |_| {
+        let args = args.iter().cloned().map(Value::Str).collect();
+        Ok(Value::List(Rc::new(RefCell::new(args))))
+    });
+
+    define_fn(env, "env", 1, |args| {
+        let name = args.str(0)?;
+        Ok(std::env::var(name).map_or(Value::Nil, Value::Str))
+    });
+
+    define_fn(env, "exit", 1, |args| match args[0] {
+        Value::Num(n) if n.fract() == 0.0 && n.abs() <= i32::MAX as f64 => {
+            Err(LoxError::Exit(n as i32))
+        }
+        _ => Err(LoxError::native("Exit code must be an integer.")),
+    });
+}
@@ -1,38 +1,204 @@
 mod ast;
 mod clock;
+mod dump;
 mod env;
 mod error;
+mod formatter;
 mod function;
+mod host;
 mod interpreter;
+mod io;
+mod limits;
+mod list;
+mod map;
+mod math;
+mod module;
+mod native;
 mod parser;
+mod platform;
 mod printer;
 mod resolver;
 mod scanner;
+mod string;
 mod token;
 mod value;
 mod visitor;
 
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
 use crate::ast::Stmt;
 pub use crate::interpreter::Interpreter;
 pub use error::LoxError;
+pub use host::HostObject;
+pub use limits::InterruptHandle;
+pub use native::Args;
+pub use platform::{DeterministicHost, Host, SystemHost};
+pub use scanner::is_incomplete;
+pub use value::{Callable, Value};
 
 pub fn interpret(source: &str) -> Result<(), LoxError> {
     let stmts = parse(source)?;
     let mut interpreter = Interpreter::new();
 
-    interpreter.interpret(stmts).map_err(Into::into)
+    interpreter.interpret(stmts)
+}
+
+/// Runs `source` like `interpret`, but collects what it prints into a
+/// string instead of writing it to stdout.
+pub fn run_capture(source: &str) -> (String, Result<(), LoxError>) {
+    let capture = Capture::default();
+    let mut interpreter = Interpreter::new();
+    interpreter.set_output(capture.clone());
+
+    let res = parse(source).and_then(|stmts| interpreter.interpret(stmts));
+    let out = String::from_utf8_lossy(&capture.0.borrow()).into_owned();
+
+    (out, res)
+}
+
+/// Evaluates snippets one after another, each seeing the variables and
+/// functions declared by the ones before. The REPL is built on it.
+///
+/// Declarations at the top of a snippet go into the interpreter's globals,
+/// which the session keeps. The resolver only tracks block and function
+/// scopes, and those never span snippets, so a fresh one per snippet sees
+/// everything it needs.
+pub struct Session {
+    interpreter: Interpreter,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session::with_interpreter(Interpreter::new())
+    }
+
+    /// Starts a session on an interpreter that has already been set up, for
+    /// example with `Interpreter::allow_io` or `Interpreter::register_fn`.
+    pub fn with_interpreter(interpreter: Interpreter) -> Self {
+        Session { interpreter }
+    }
+
+    pub fn interpreter(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+
+    /// Runs a snippet, returning the value of its last statement if that is
+    /// an expression. The semicolon ending the snippet may be left off, so
+    /// `1 + 2` evaluates to 3.
+    pub fn eval(&mut self, source: &str) -> Result<Option<Value>, LoxError> {
+        let stmts = match parse(source) {
+            Ok(stmts) => stmts,
+            // The semicolon goes on a line of its own, so that it can't end
+            // up inside a trailing `//` comment.
+            Err(e) => parse(&format!("{}\n;", source)).map_err(|_| e)?,
+        };
+
+        self.interpreter.eval(stmts)
+    }
+
+    /// Runs a whole program, such as a file, in the session.
+    pub fn run(&mut self, source: &str) -> Result<(), LoxError> {
+        let stmts = parse(source)?;
+        self.interpreter.interpret(stmts)
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
+
+/// Parses `source` as a single expression and prints its syntax tree as an
+/// S-expression.
+pub fn expression_ast(source: &str) -> Result<String, LoxError> {
+    let stmts = parse(&format!("{};", source.trim_end().trim_end_matches(';')))?;
+
+    match stmts.as_slice() {
+        [Stmt::Expr(expr)] => Ok(expr.to_string()),
+        _ => Err(LoxError::Compile("Expect a single expression.".into())),
+    }
+}
+
+/// Dumps the syntax tree of a program, with source lines, as JSON. The
+/// format is described on `dump::Node`.
+pub fn ast_json(source: &str) -> Result<String, LoxError> {
+    Ok(dump(source)?.to_json())
+}
+
+/// Reads back a syntax tree dumped by `ast_json`, so that a tool can change
+/// it and hand the result to `Interpreter::interpret`.
+pub fn load_ast_json(json: &str) -> Result<Vec<Stmt>, LoxError> {
+    dump::load(json).map_err(|e| LoxError::Compile(format!("Invalid syntax tree: {}", e)))
+}
+
+/// Dumps the syntax tree of a program, with source lines, as S-expressions.
+pub fn ast_sexpr(source: &str) -> Result<String, LoxError> {
+    Ok(dump(source)?.to_sexpr())
+}
+
+fn dump(source: &str) -> Result<dump::Node, LoxError> {
+    let (stmts, layout, errors) = parser::parse_with_layout(source);
+    check(errors)?;
+
+    Ok(dump::program(&stmts, &layout))
+}
+
+/// Reprints a program in the canonical style, keeping its comments.
+/// Formatting the result again leaves it unchanged.
+pub fn format(source: &str) -> Result<String, LoxError> {
+    let (stmts, layout, errors) = parser::parse_with_layout(source);
+    check(errors)?;
+
+    Ok(formatter::format(&stmts, layout))
 }
 
 pub fn parse(source: &str) -> Result<Vec<Stmt>, LoxError> {
     let (stmts, errors) = parser::parse(source);
+    check(errors)?;
+
+    Ok(stmts)
+}
 
+fn check(errors: Vec<LoxError>) -> Result<(), LoxError> {
     if errors.is_empty() {
-        return Ok(stmts);
+        return Ok(());
+    }
+
+    // Report every error, one per line, rather than just the first.
+    let msgs = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+    Err(LoxError::Compile(msgs.join("\n")))
+}
+
+#[derive(Clone, Default)]
+struct Capture(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
     }
 
-    for error in &errors[..errors.len() - 1] {
-        eprintln!("{}", error);
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
+}
 
-    Err(errors.last().cloned().unwrap())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eval_without_semicolon() {
+        let mut session = Session::new();
+
+        assert_eq!(session.eval("1 + 2").unwrap(), Some(Value::Num(3.0)));
+        assert_eq!(
+            session.eval("1 + 2 // note").unwrap(),
+            Some(Value::Num(3.0))
+        );
+        assert_eq!(session.eval("var a = 1; // note").unwrap(), None);
+        assert!(session.eval("1 +").is_err());
+    }
 }
@@ -0,0 +1,103 @@
+use std::fmt::{self, Write};
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::list::List;
+use crate::map::{Key, MapRef};
+use crate::value::Value;
+
+/// The default maximum call depth. It leaves plenty of headroom on a 2MB
+/// thread stack, even in debug builds.
+pub const MAX_CALL_DEPTH: usize = 256;
+
+/// Bounds on how much work a program may do. Steps and heap use are counted
+/// from the start of each `Interpreter::interpret` or `Interpreter::call`.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub steps: Option<u64>,
+    pub call_depth: usize,
+    pub heap: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            steps: None,
+            call_depth: MAX_CALL_DEPTH,
+            heap: None,
+        }
+    }
+}
+
+/// Stops a running program from another thread. The program fails with an
+/// "Interrupted." error at its next statement. If none is running, the next
+/// one stops before its first statement.
+#[derive(Debug, Clone, Default)]
+pub struct InterruptHandle(Arc<AtomicBool>);
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    pub(crate) fn take(&self) -> bool {
+        self.0.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// What one element of a list counts for against the heap limit.
+pub const LIST_SLOT: usize = mem::size_of::<Value>();
+
+/// What one entry of a map counts for against the heap limit.
+pub const MAP_ENTRY: usize = mem::size_of::<Key>() + mem::size_of::<Value>();
+
+/// The length in bytes of `value`'s printed form, found without building
+/// the string.
+pub fn display_size(value: &impl fmt::Display) -> usize {
+    struct Count(usize);
+
+    impl Write for Count {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += s.len();
+            Ok(())
+        }
+    }
+
+    let mut count = Count(0);
+    write!(count, "{}", value).unwrap();
+    count.0
+}
+
+/// An estimate of the bytes a value owns directly. Elements of collections
+/// are not followed, since they were accounted for when they were created.
+pub trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for Value {
+    fn heap_size(&self) -> usize {
+        match self {
+            Value::Str(s) => s.len(),
+            Value::List(list) => list.heap_size(),
+            Value::Map(map) => map.heap_size(),
+            _ => 0,
+        }
+    }
+}
+
+impl HeapSize for List {
+    fn heap_size(&self) -> usize {
+        self.borrow().len() * LIST_SLOT
+    }
+}
+
+impl HeapSize for MapRef {
+    fn heap_size(&self) -> usize {
+        self.borrow().len() * MAP_ENTRY
+    }
+}
@@ -0,0 +1,79 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::native::{Method, NativeMethod};
+use crate::value::Value;
+use crate::LoxError;
+
+pub type List = Rc<RefCell<Vec<Value>>>;
+
+pub fn method(list: &List, name: &str) -> Option<Value> {
+    let (arity, method): (usize, Method<List>) = match name {
+        "len" => (0, len),
+        "push" => (1, push),
+        "pop" => (0, pop),
+        "insert" => (2, insert),
+        "remove" => (1, remove),
+        _ => return None,
+    };
+
+    Some(Value::Call(Rc::new(NativeMethod::new(
+        list.clone(),
+        arity,
+        method,
+    ))))
+}
+
+pub fn get(list: &List, index: &Value) -> Result<Value, &'static str> {
+    let list = list.borrow();
+    let idx = to_index(index, list.len())?;
+    Ok(list[idx].clone())
+}
+
+pub fn set(list: &List, index: &Value, value: Value) -> Result<(), &'static str> {
+    let mut list = list.borrow_mut();
+    let idx = to_index(index, list.len())?;
+    list[idx] = value;
+    Ok(())
+}
+
+fn to_index(index: &Value, len: usize) -> Result<usize, &'static str> {
+    match index {
+        Value::Num(n) if n.fract() != 0.0 => Err("List index must be an integer."),
+        Value::Num(n) if *n < 0.0 || *n >= len as f64 => Err("List index out of bounds."),
+        Value::Num(n) => Ok(*n as usize),
+        _ => Err("List index must be a number."),
+    }
+}
+
+fn len(list: &List, _: Vec<Value>) -> Result<Value, LoxError> {
+    Ok(Value::Num(list.borrow().len() as f64))
+}
+
+fn push(list: &List, mut args: Vec<Value>) -> Result<Value, LoxError> {
+    list.borrow_mut().push(args.remove(0));
+    Ok(Value::Nil)
+}
+
+fn pop(list: &List, _: Vec<Value>) -> Result<Value, LoxError> {
+    list.borrow_mut()
+        .pop()
+        .ok_or_else(|| LoxError::native("Can't pop from an empty list."))
+}
+
+fn insert(list: &List, mut args: Vec<Value>) -> Result<Value, LoxError> {
+    let value = args.pop().unwrap();
+    let mut list = list.borrow_mut();
+
+    // Inserting at the end is allowed, so check against one past it.
+    let idx = to_index(&args[0], list.len() + 1).map_err(LoxError::native)?;
+    list.insert(idx, value);
+
+    Ok(Value::Nil)
+}
+
+fn remove(list: &List, args: Vec<Value>) -> Result<Value, LoxError> {
+    let mut list = list.borrow_mut();
+    let idx = to_index(&args[0], list.len()).map_err(LoxError::native)?;
+    Ok(list.remove(idx))
+}
@@ -1,61 +1,318 @@
-use std::io::{self, BufRead, Write};
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 
-use rlox::{Interpreter, LoxError};
+use rlox::{DeterministicHost, Interpreter, LoxError, Session, Value};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+#[derive(Default)]
+struct Options {
+    allow_io: bool,
+    deterministic: bool,
+}
 
 fn main() {
-    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut args = std::env::args().skip(1).peekable();
+    let mut options = Options::default();
+
+    while let Some(flag) = args.next_if(|arg| arg.starts_with("--")) {
+        match flag.as_str() {
+            "--allow-io" => options.allow_io = true,
+            "--deterministic" => options.deterministic = true,
+            _ => usage(),
+        }
+    }
+
+    // Everything after the script path is passed through to the script.
+    match args.next() {
+        None => repl(&options),
+        Some(cmd) if cmd == "fmt" => fmt(args),
+        Some(cmd) if cmd == "ast" => ast(args),
+        Some(path) => run_file(&path, &options, args.collect()),
+    }
+}
+
+// `rlox fmt [--check] [paths...]` formats files in place. With `--check` it
+// only lists the files that would change, and exits with 1 if there are
+// any. Without paths it formats stdin to stdout.
+fn fmt(args: impl Iterator<Item = String>) {
+    let mut check = false;
+    let mut paths = vec![];
+
+    for arg in args {
+        match arg.as_str() {
+            "--check" => check = true,
+            _ if arg.starts_with("--") => usage(),
+            _ => paths.push(arg),
+        }
+    }
+
+    if paths.is_empty() {
+        let mut src = String::new();
+        if let Err(e) = std::io::stdin().read_to_string(&mut src) {
+            eprintln!("Could not read stdin: {}.", e);
+            exit(66);
+        }
+
+        let formatted = format_source("<stdin>", &src);
+        if !check {
+            print!("{}", formatted);
+        } else if formatted != src {
+            println!("<stdin>");
+            exit(1);
+        }
+        return;
+    }
+
+    let mut unformatted = false;
+
+    for path in paths {
+        let src = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+            eprintln!("Could not read '{}': {}.", path, e);
+            exit(66)
+        });
+
+        let formatted = format_source(&path, &src);
+        if formatted == src {
+            continue;
+        }
 
-    match args.len() {
-        0 => repl(),
-        1 => run_file(args.first().unwrap()),
-        _ => usage(),
+        if check {
+            println!("{}", path);
+            unformatted = true;
+        } else if let Err(e) = std::fs::write(&path, formatted) {
+            eprintln!("Could not write '{}': {}.", path, e);
+            exit(74);
+        }
+    }
+
+    if unformatted {
+        exit(1);
     }
 }
 
-fn run_file(path: &str) {
+// `rlox ast [--format json|sexpr] path` prints the syntax tree of a file.
+fn ast(mut args: impl Iterator<Item = String>) {
+    let mut json = false;
+    let mut path = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => match args.next().as_deref() {
+                Some("json") => json = true,
+                Some("sexpr") => json = false,
+                _ => usage(),
+            },
+            _ if arg.starts_with("--") || path.is_some() => usage(),
+            _ => path = Some(arg),
+        }
+    }
+
+    let Some(path) = path else { usage() };
+    let src = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        eprintln!("Could not read '{}': {}.", path, e);
+        exit(66)
+    });
+
+    let res = if json {
+        rlox::ast_json(&src)
+    } else {
+        rlox::ast_sexpr(&src)
+    };
+
+    match res {
+        Ok(out) => print!("{}", out),
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(65);
+        }
+    }
+}
+
+fn format_source(path: &str, src: &str) -> String {
+    rlox::format(src).unwrap_or_else(|e| {
+        eprintln!("{}:", path);
+        eprintln!("{}", e);
+        exit(65)
+    })
+}
+
+fn run_file(path: &str, options: &Options, script_args: Vec<String>) {
     let src = std::fs::read_to_string(path).unwrap();
 
-    if let Err(e) = rlox::interpret(src.trim()) {
-        match e {
-            LoxError::Compile(e) => {
-                eprintln!("{}", e);
-                exit(65);
+    let mut interpreter = Interpreter::new();
+    interpreter.set_script_path(path);
+    configure(&mut interpreter, options, script_args);
+
+    let res = rlox::parse(src.trim()).and_then(|stmts| interpreter.interpret(stmts));
+
+    if let Err(e) = res {
+        exit(report(e));
+    }
+}
+
+// Prints an error that ended a run, and returns the exit status for it.
+fn report(e: LoxError) -> i32 {
+    match e {
+        LoxError::Compile(e) => {
+            eprintln!("{}", e);
+            65
+        }
+        LoxError::Runtime(msg, line) | LoxError::Limit(msg, line) => {
+            eprintln!("{}", msg);
+            eprintln!("[line {}]", line);
+            70
+        }
+        LoxError::Throw(value, line) => {
+            eprintln!("Uncaught exception: {}", value);
+            eprintln!("[line {}]", line);
+            70
+        }
+        LoxError::Exit(code) => code,
+        _ => panic!("RET leaked"),
+    }
+}
+
+fn configure(interpreter: &mut Interpreter, options: &Options, script_args: Vec<String>) {
+    // Imports not found next to the importing file are looked up in each
+    // directory listed in the LOX_PATH environment variable.
+    if let Some(paths) = std::env::var_os("LOX_PATH") {
+        std::env::split_paths(&paths).for_each(|dir| interpreter.add_search_path(dir));
+    }
+
+    if options.allow_io {
+        interpreter.allow_io(script_args);
+    }
+
+    if options.deterministic {
+        interpreter.set_host(DeterministicHost::default());
+    }
+}
+
+const HELP: &str = "\
+Enter declarations and statements to run them, or an expression to print
+its value. Input with an open bracket, string or comment continues on the
+next line.
+
+:help          Show this message
+:reset         Start over with a fresh interpreter
+:load <path>   Run a file in the current session
+:ast <expr>    Print the syntax tree of an expression
+:disasm <expr> Print the bytecode of an expression
+:quit          Exit the REPL";
+
+fn repl(options: &Options) {
+    let mut editor = DefaultEditor::new().expect("Could not start the line editor");
+    let history = history_path();
+    if let Some(path) = &history {
+        let _ = editor.load_history(path);
+    }
+
+    let mut session = new_session(options);
+
+    while let Some(input) = read_input(&mut editor) {
+        let _ = editor.add_history_entry(input.as_str());
+
+        let input = input.trim();
+        let res = if matches!(input, ":quit" | ":q") {
+            break;
+        } else if let Some(cmd) = input.strip_prefix(':') {
+            command(&mut session, options, cmd)
+        } else if input.is_empty() {
+            Ok(())
+        } else {
+            echo(&mut session, input)
+        };
+
+        match res {
+            Err(LoxError::Exit(code)) => {
+                save_history(&mut editor, &history);
+                exit(code)
             }
-            LoxError::Runtime(msg, line) => {
-                eprintln!("{}", msg);
-                eprintln!("[line {}]", line);
-                exit(70)
+            Err(e) => {
+                report(e);
             }
-            _ => panic!("RET leaked"),
+            Ok(()) => {}
         }
     }
+
+    save_history(&mut editor, &history);
 }
 
-fn repl() {
-    let prompt = || {
-        print!("> ");
-        io::stdout().flush().unwrap();
-    };
+fn command(session: &mut Session, options: &Options, cmd: &str) -> Result<(), LoxError> {
+    let (name, arg) = cmd.split_once(char::is_whitespace).unwrap_or((cmd, ""));
+
+    match name {
+        "help" => println!("{}", HELP),
+        "reset" => *session = new_session(options),
+        "load" => return load(session, arg.trim()),
+        "ast" => println!("{}", rlox::expression_ast(arg)?),
+        "disasm" => println!("The bytecode VM is not part of this build."),
+        _ => println!("Unknown command. Try :help."),
+    }
+
+    Ok(())
+}
 
+fn new_session(options: &Options) -> Session {
     let mut interpreter = Interpreter::new();
+    configure(&mut interpreter, options, vec![]);
+    Session::with_interpreter(interpreter)
+}
 
-    prompt();
-    for line in io::stdin().lock().lines() {
-        match rlox::parse(&line.unwrap()) {
-            Ok(stmts) => {
-                if let Err(e) = interpreter.interpret(stmts) {
-                    println!("{}", e)
-                }
+// Reads one complete entry, prompting for more lines while the input is
+// unfinished. Returns `None` once the user is done.
+fn read_input(editor: &mut DefaultEditor) -> Option<String> {
+    let mut input = match editor.readline("> ") {
+        Ok(line) => line,
+        Err(ReadlineError::Interrupted) => return Some(String::new()),
+        Err(_) => return None,
+    };
+
+    while !input.trim_start().starts_with(':') && rlox::is_incomplete(&input) {
+        match editor.readline("... ") {
+            Ok(line) => {
+                input.push('\n');
+                input.push_str(&line);
             }
-            Err(e) => println!("{}", e),
+            // Ctrl-C drops the unfinished entry
+            Err(ReadlineError::Interrupted) => return Some(String::new()),
+            Err(_) => break,
         }
+    }
+
+    Some(input)
+}
+
+fn echo(session: &mut Session, src: &str) -> Result<(), LoxError> {
+    match session.eval(src)? {
+        Some(Value::Nil) | None => {}
+        Some(value) => println!("{}", value),
+    }
+    Ok(())
+}
+
+fn load(session: &mut Session, path: &str) -> Result<(), LoxError> {
+    let src = std::fs::read_to_string(path)
+        .map_err(|e| LoxError::Compile(format!("Could not read '{}': {}.", path, e)))?;
+
+    session.run(&src)
+}
+
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| Path::new(&home).join(".rlox_history"))
+}
 
-        prompt();
+fn save_history(editor: &mut DefaultEditor, path: &Option<PathBuf>) {
+    if let Some(path) = path {
+        let _ = editor.save_history(path);
     }
 }
 
-fn usage() {
-    eprintln!("Usage: rlox [path]");
+fn usage() -> ! {
+    eprintln!("Usage: rlox [--allow-io] [--deterministic] [path [args...]]");
+    eprintln!("       rlox fmt [--check] [paths...]");
+    eprintln!("       rlox ast [--format json|sexpr] path");
     exit(64);
 }
@@ -0,0 +1,158 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use crate::native::{Method, NativeMethod};
+use crate::value::Value;
+use crate::LoxError;
+
+pub type MapRef = Rc<RefCell<Map>>;
+
+/// A hash map that iterates in insertion order, so that printing a map or
+/// looping over its keys gives the same output on every run.
+#[derive(Debug, Default)]
+pub struct Map {
+    entries: Vec<(Key, Value)>,
+    index: HashMap<Key, usize>,
+}
+
+/// The hashable subset of values. Numbers are keyed by their bits, with
+/// `-0` folded into `0` so that keys that compare equal also hash equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    Nil,
+    Bool(bool),
+    Num(u64),
+    Str(String),
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Map::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, key: &Key) -> Option<&Value> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn insert(&mut self, key: Key, value: Value) {
+        match self.index.get(&key) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &Key) -> Option<Value> {
+        let i = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+
+        for idx in self.index.values_mut() {
+            if *idx > i {
+                *idx -= 1;
+            }
+        }
+
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Key, &Value)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+}
+
+impl TryFrom<&Value> for Key {
+    type Error = &'static str;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Nil => Ok(Key::Nil),
+            Value::Bool(b) => Ok(Key::Bool(*b)),
+            Value::Num(n) if n.is_nan() => Err("Map keys can't be NaN."),
+            Value::Num(n) if *n == 0.0 => Ok(Key::Num(0f64.to_bits())),
+            Value::Num(n) => Ok(Key::Num(n.to_bits())),
+            Value::Str(s) => Ok(Key::Str(s.clone())),
+            _ => Err("Map keys must be nil, booleans, numbers or strings."),
+        }
+    }
+}
+
+impl From<&Key> for Value {
+    fn from(key: &Key) -> Self {
+        match key {
+            Key::Nil => Value::Nil,
+            Key::Bool(b) => Value::Bool(*b),
+            Key::Num(bits) => Value::Num(f64::from_bits(*bits)),
+            Key::Str(s) => Value::Str(s.clone()),
+        }
+    }
+}
+
+pub fn method(map: &MapRef, name: &str) -> Option<Value> {
+    let (arity, method): (usize, Method<MapRef>) = match name {
+        "len" => (0, len),
+        "keys" => (0, keys),
+        "values" => (0, values),
+        "has" => (1, has),
+        "remove" => (1, remove),
+        _ => return None,
+    };
+
+    Some(Value::Call(Rc::new(NativeMethod::new(
+        map.clone(),
+        arity,
+        method,
+    ))))
+}
+
+pub fn get(map: &MapRef, key: &Value) -> Result<Value, String> {
+    let k = Key::try_from(key)?;
+
+    map.borrow()
+        .get(&k)
+        .cloned()
+        .ok_or_else(|| format!("Undefined key '{}'.", key))
+}
+
+// Unhashable values can never be keys, so they are simply absent.
+pub fn contains(map: &MapRef, key: &Value) -> bool {
+    match Key::try_from(key) {
+        Ok(key) => map.borrow().get(&key).is_some(),
+        Err(_) => false,
+    }
+}
+
+pub fn set(map: &MapRef, key: &Value, value: Value) -> Result<(), String> {
+    map.borrow_mut().insert(Key::try_from(key)?, value);
+    Ok(())
+}
+
+fn len(map: &MapRef, _: Vec<Value>) -> Result<Value, LoxError> {
+    Ok(Value::Num(map.borrow().len() as f64))
+}
+
+fn keys(map: &MapRef, _: Vec<Value>) -> Result<Value, LoxError> {
+    let keys = map.borrow().iter().map(|(k, _)| k.into()).collect();
+    Ok(Value::List(Rc::new(RefCell::new(keys))))
+}
+
+fn values(map: &MapRef, _: Vec<Value>) -> Result<Value, LoxError> {
+    let values = map.borrow().iter().map(|(_, v)| v.clone()).collect();
+    Ok(Value::List(Rc::new(RefCell::new(values))))
+}
+
+fn has(map: &MapRef, args: Vec<Value>) -> Result<Value, LoxError> {
+    Ok(Value::Bool(contains(map, &args[0])))
+}
+
+fn remove(map: &MapRef, args: Vec<Value>) -> Result<Value, LoxError> {
+    let key = Key::try_from(&args[0]).map_err(LoxError::native)?;
+    Ok(map.borrow_mut().remove(&key).unwrap_or(Value::Nil))
+}
@@ -0,0 +1,82 @@
+use std::f64::consts;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::env::Env;
+use crate::native::{define_fn, Args};
+use crate::value::{Callable, Value};
+use crate::{Interpreter, LoxError};
+
+/// Defines the math natives and constants in `env`.
+pub fn define(env: &mut Env) {
+    env.define("PI", Value::Num(consts::PI));
+    env.define("INF", Value::Num(f64::INFINITY));
+    env.define("NAN", Value::Num(f64::NAN));
+
+    unary(env, "sqrt", f64::sqrt);
+    unary(env, "floor", f64::floor);
+    unary(env, "ceil", f64::ceil);
+    unary(env, "abs", f64::abs);
+    unary(env, "sin", f64::sin);
+    unary(env, "cos", f64::cos);
+    unary(env, "log", f64::ln);
+
+    binary(env, "pow", f64::powf);
+    binary(env, "min", f64::min);
+    binary(env, "max", f64::max);
+
+    env.define("random", Value::Call(Rc::new(Random)));
+    env.define("seed", Value::Call(Rc::new(Seed)));
+}
+
+fn unary(env: &mut Env, name: &str, f: fn(f64) -> f64) {
+    define_fn(env, name, 1, move |args| Ok(Value::Num(f(args.num(0)?))));
+}
+
+fn binary(env: &mut Env, name: &str, f: fn(f64, f64) -> f64) {
+    define_fn(env, name, 2, move |args| {
+        Ok(Value::Num(f(args.num(0)?, args.num(1)?)))
+    });
+}
+
+// `random` and `seed` go through the interpreter's `Host`, so that a
+// deterministic host makes them reproducible.
+#[derive(Debug)]
+struct Random;
+
+impl Callable for Random {
+    fn arity(&self) -> usize {
+        0
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, _: Vec<Value>) -> Result<Value, LoxError> {
+        Ok(Value::Num(interpreter.host().random()))
+    }
+}
+
+impl fmt::Display for Random {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<native fn>")
+    }
+}
+
+#[derive(Debug)]
+struct Seed;
+
+impl Callable for Seed {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, LoxError> {
+        let seed = Args::from(args).num(0)?;
+        interpreter.host().seed(seed.to_bits());
+        Ok(Value::Nil)
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<native fn>")
+    }
+}
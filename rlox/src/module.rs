@@ -0,0 +1,37 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::env::Env;
+use crate::value::Value;
+
+/// The namespace created by `import "path" as name;`. It owns the globals
+/// the imported file defined; lookups don't fall through to the builtins.
+#[derive(Debug)]
+pub struct Module {
+    name: String,
+    env: Rc<RefCell<Env>>,
+}
+
+impl Module {
+    pub fn new(name: &str, env: Rc<RefCell<Env>>) -> Self {
+        Module {
+            name: name.into(),
+            env,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.env.borrow().get_own(name)
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<module {}>", self.name)
+    }
+}
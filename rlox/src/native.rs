@@ -0,0 +1,223 @@
+use std::fmt::{self, Debug};
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::env::Env;
+use crate::host::HostObject;
+use crate::limits::HeapSize;
+use crate::value::{Callable, Value};
+use crate::{Interpreter, LoxError};
+
+pub type Method<T> = fn(&T, Vec<Value>) -> Result<Value, LoxError>;
+
+type NativeBody = dyn Fn(Args) -> Result<Value, LoxError>;
+
+/// Defines a closure-backed native function called `name` in `env`.
+pub fn define_fn(
+    env: &mut Env,
+    name: &str,
+    arity: usize,
+    fun: impl Fn(Args) -> Result<Value, LoxError> + 'static,
+) {
+    let native = NativeFn::new(name, arity, fun);
+    env.define(name, Value::Call(Rc::new(native)));
+}
+
+/// The arguments a native function was called with.
+///
+/// It derefs to a slice of values, and the typed accessors turn a wrong
+/// argument type into a runtime error:
+///
+/// ```ignore
+/// interpreter.register_fn("hypot", 2, |args| {
+///     Ok(Value::Num(args.num(0)?.hypot(args.num(1)?)))
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    values: Vec<Value>,
+    // What is left of the run's heap limit, if it has one
+    heap_left: Option<usize>,
+}
+
+impl Args {
+    pub(crate) fn new(values: Vec<Value>, heap_left: Option<usize>) -> Self {
+        Args { values, heap_left }
+    }
+
+    /// Fails with "Out of memory." if the run's heap limit leaves less than
+    /// `bytes`. A native returning a large value should call this before
+    /// building it, since it is only charged for the value afterwards.
+    pub fn reserve(&self, bytes: usize) -> Result<(), LoxError> {
+        match self.heap_left {
+            Some(left) if bytes > left => Err(LoxError::Limit("Out of memory.".into(), 0)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn num(&self, idx: usize) -> Result<f64, LoxError> {
+        match self.arg(idx)? {
+            Value::Num(n) => Ok(*n),
+            _ => Err(Self::expected(idx, "a number")),
+        }
+    }
+
+    pub fn str(&self, idx: usize) -> Result<&str, LoxError> {
+        match self.arg(idx)? {
+            Value::Str(s) => Ok(s),
+            _ => Err(Self::expected(idx, "a string")),
+        }
+    }
+
+    pub fn bool(&self, idx: usize) -> Result<bool, LoxError> {
+        match self.arg(idx)? {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(Self::expected(idx, "a boolean")),
+        }
+    }
+
+    /// The argument as a host object of type `T`.
+    pub fn host<T: HostObject>(&self, idx: usize) -> Result<&T, LoxError> {
+        match self.arg(idx)?.downcast_host() {
+            Some(object) => Ok(object),
+            None => Err(Self::expected(idx, "a host object")),
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<Value> {
+        self.values
+    }
+
+    fn arg(&self, idx: usize) -> Result<&Value, LoxError> {
+        self.values
+            .get(idx)
+            .ok_or_else(|| LoxError::native(format!("Missing argument {}.", idx + 1)))
+    }
+
+    pub(crate) fn expected(idx: usize, what: &str) -> LoxError {
+        LoxError::native(format!("Argument {} must be {}.", idx + 1, what))
+    }
+}
+
+impl Deref for Args {
+    type Target = [Value];
+
+    fn deref(&self) -> &[Value] {
+        &self.values
+    }
+}
+
+impl From<Vec<Value>> for Args {
+    fn from(values: Vec<Value>) -> Self {
+        Args::new(values, None)
+    }
+}
+
+/// A native function backed by a Rust closure.
+pub struct NativeFn {
+    name: String,
+    arity: usize,
+    variadic: bool,
+    fun: Box<NativeBody>,
+}
+
+impl NativeFn {
+    pub fn new(
+        name: &str,
+        arity: usize,
+        fun: impl Fn(Args) -> Result<Value, LoxError> + 'static,
+    ) -> Self {
+        NativeFn {
+            name: name.into(),
+            arity,
+            variadic: false,
+            fun: Box::new(fun),
+        }
+    }
+
+    /// A native taking `min_arity` or more arguments.
+    pub fn variadic(
+        name: &str,
+        min_arity: usize,
+        fun: impl Fn(Args) -> Result<Value, LoxError> + 'static,
+    ) -> Self {
+        NativeFn {
+            variadic: true,
+            ..NativeFn::new(name, min_arity, fun)
+        }
+    }
+}
+
+impl Callable for NativeFn {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn variadic(&self) -> bool {
+        self.variadic
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, LoxError> {
+        let value = (self.fun)(Args::new(args, interpreter.heap_left()))?;
+        interpreter.charge(value.heap_size())?;
+        Ok(value)
+    }
+}
+
+impl fmt::Debug for NativeFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeFn")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .field("variadic", &self.variadic)
+            .finish()
+    }
+}
+
+impl fmt::Display for NativeFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<native fn>")
+    }
+}
+
+/// A native method bound to the receiver it was looked up on, such as the
+/// value of `xs.push` for a list `xs`.
+#[derive(Debug)]
+pub struct NativeMethod<T> {
+    receiver: T,
+    arity: usize,
+    method: Method<T>,
+}
+
+impl<T> NativeMethod<T> {
+    pub fn new(receiver: T, arity: usize, method: Method<T>) -> Self {
+        NativeMethod {
+            receiver,
+            arity,
+            method,
+        }
+    }
+}
+
+impl<T: Debug + HeapSize> Callable for NativeMethod<T> {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    // Methods like `push` grow their receiver, so that counts as allocation
+    // along with whatever the method returns.
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, LoxError> {
+        let before = self.receiver.heap_size();
+        let value = (self.method)(&self.receiver, args)?;
+        let grown = self.receiver.heap_size().saturating_sub(before);
+
+        interpreter.charge(grown + value.heap_size())?;
+        Ok(value)
+    }
+}
+
+impl<T> fmt::Display for NativeMethod<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<native fn>")
+    }
+}
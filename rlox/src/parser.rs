@@ -1,5 +1,8 @@
-use crate::ast::{Expr, Lit, Stmt};
-use crate::scanner;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::ast::{BinOp, Expr, Lit, Stmt, UnOp};
+use crate::scanner::{self, ScanError};
 use crate::token::{
     Token,
     TokenKind::{self, *},
@@ -11,29 +14,187 @@ type Result<T> = std::result::Result<T, LoxError>;
 pub struct Parser<'a> {
     tokens: Vec<Token<'a>>,
     idx: usize,
+    // Doc comments, keyed by the index of the token that follows them.
+    docs: HashMap<usize, String>,
+    // Scan errors are reported up front so that parsing can carry on
+    // without the offending token.
+    scan_errors: Vec<Token<'a>>,
+    layout: Layout,
+}
+
+/// Where things were in the source, for tools that print a program back
+/// out, such as the formatter. Running a program doesn't need it.
+#[derive(Debug, Default)]
+pub struct Layout {
+    /// Where each statement is, in the order the parser started them, which
+    /// is also the order a tree walk reaches them in.
+    pub spans: Vec<Span>,
+    /// Line and column of the `}` closing each block, in the order they
+    /// were closed.
+    pub block_ends: Vec<(u64, u64)>,
+    pub comments: Vec<Comment>,
+    /// How each number and string literal was written, in the order they
+    /// were parsed. Strings are without their quotes, and an interpolated
+    /// string gives one entry per piece of text between `${}`s.
+    pub literals: Vec<Literal>,
+    /// How many pieces each interpolated string was split into, in the
+    /// order they start.
+    pub interpolations: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Span {
+    pub line: u64,
+    pub end_line: u64,
+    /// The column the first token starts at, counting characters from 1.
+    pub column: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Comment {
+    /// The comment with its delimiters.
+    pub text: String,
+    pub line: u64,
+    pub end_line: u64,
+    /// The column it starts at.
+    pub column: u64,
+    /// Whether it follows other code on its first line.
+    pub trailing: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Literal {
+    pub text: String,
+    /// Where the token starts. For a piece of an interpolated string, that
+    /// is the `"` or `}` before it.
+    pub line: u64,
+    pub column: u64,
 }
 
 pub fn parse(source: &str) -> (Vec<Stmt>, Vec<LoxError>) {
+    let (stmts, _, errors) = parse_with_layout(source);
+    (stmts, errors)
+}
+
+pub fn parse_with_layout(source: &str) -> (Vec<Stmt>, Layout, Vec<LoxError>) {
     let tokens = scanner::tokenize(source).collect();
-    Parser::new(tokens).parse()
+    let mut parser = Parser::new(tokens);
+    let (stmts, errors) = parser.parse();
+    (stmts, parser.layout, errors)
 }
 
 impl<'a> Parser<'a> {
     const MAX_FN_ARGUMENT_COUNT: usize = 255;
 
     pub fn new(tokens: Vec<Token<'a>>) -> Self {
-        Parser { tokens, idx: 0 }
+        let mut docs = HashMap::new();
+        let mut lines = vec![];
+        // The line the last doc comment line is on.
+        let mut doc_line = 0;
+        let mut kept = Vec::with_capacity(tokens.len());
+        let mut scan_errors = vec![];
+        let mut layout = Layout::default();
+        let mut last_line = 0;
+
+        for token in tokens {
+            // Comment tokens carry the line they end on.
+            let text = match token.kind {
+                COMMENT(text) => Some(text.to_string()),
+                DOC_COMMENT(text) => Some(format!("///{}", text)),
+                _ => None,
+            };
+            if let Some(text) = text {
+                let line = token.line - text.matches('\n').count() as u64;
+                layout.comments.push(Comment {
+                    text,
+                    line,
+                    end_line: token.line,
+                    column: token.column,
+                    trailing: line == last_line,
+                });
+            }
+            last_line = token.line;
+
+            // A doc comment only belongs to what follows right after it,
+            // with no blank line or regular comment in between.
+            if !matches!(token.kind, ERROR(_)) && token.line > doc_line + 1 {
+                lines.clear();
+            }
+
+            match token.kind {
+                COMMENT(_) => {
+                    lines.clear();
+                    continue;
+                }
+                DOC_COMMENT(text) => {
+                    lines.push(text.strip_prefix(' ').unwrap_or(text));
+                    doc_line = token.line;
+                    continue;
+                }
+                ERROR(ScanError::UnterminatedInterpolation) => {
+                    // Everything after the open `${` was scanned as part of
+                    // it, so none of that string is worth parsing.
+                    let opened = kept.iter().rposition(|t: &Token| {
+                        matches!(t.kind, INTERPOLATION(_))
+                            && (t.line, t.column) == (token.line, token.column)
+                    });
+                    if let Some(i) = opened {
+                        kept.truncate(i);
+                        docs.retain(|&k, _| k < i);
+                    }
+                    scan_errors.push(token);
+                    continue;
+                }
+                ERROR(_) => {
+                    scan_errors.push(token);
+                    continue;
+                }
+                _ => {}
+            }
+
+            if !lines.is_empty() {
+                docs.insert(kept.len(), lines.join("\n"));
+                lines.clear();
+            }
+            kept.push(token);
+        }
+
+        Parser {
+            tokens: kept,
+            idx: 0,
+            docs,
+            scan_errors,
+            layout,
+        }
     }
 
     fn parse(&mut self) -> (Vec<Stmt>, Vec<LoxError>) {
         let mut stmts = vec![];
-        let mut errors = vec![];
+        let mut errors: Vec<_> = self
+            .scan_errors
+            .iter()
+            .map(|token| self.parse_error(token.clone(), ""))
+            .collect();
+        // When a string or comment ran to the end, the input stops early
+        // and missing the rest of it is no news.
+        let ran_out = self.scan_errors.iter().any(|token| {
+            matches!(
+                token.kind,
+                ERROR(
+                    ScanError::UnterminatedString
+                        | ScanError::UnterminatedInterpolation
+                        | ScanError::UnterminatedComment
+                )
+            )
+        });
 
         while !self.at_end() {
             match self.declaration() {
                 Ok(stmt) => stmts.push(stmt),
                 Err(e) => {
-                    errors.push(e);
+                    if !(ran_out && self.at_end()) {
+                        errors.push(e);
+                    }
                     self.synchronize();
                 }
             }
@@ -43,17 +204,51 @@ impl<'a> Parser<'a> {
     }
 
     fn declaration(&mut self) -> Result<Stmt> {
-        match self.peek().kind {
+        let span = self.begin_span();
+
+        let stmt = match self.peek().kind {
             VAR => {
                 self.advance();
                 self.var_declaration()
             }
-            FUN => {
+            IMPORT => {
                 self.advance();
-                self.function("function")
+                self.import_declaration()
             }
-            _ => self.statement(),
-        }
+            FROM => {
+                self.advance();
+                self.selective_import_declaration()
+            }
+            // A `fun` without a name is a lambda in an expression statement.
+            FUN if matches!(self.peek_at(1), Some(IDENTIFIER(_))) => {
+                let doc = self.docs.remove(&self.idx);
+                self.advance();
+                self.function("function", doc)
+            }
+            _ => {
+                // The statement records its own span.
+                self.layout.spans.pop();
+                return self.statement();
+            }
+        }?;
+
+        self.end_span(span);
+        Ok(stmt)
+    }
+
+    fn begin_span(&mut self) -> usize {
+        let line = self.peek().line;
+        let column = self.peek().column;
+        self.layout.spans.push(Span {
+            line,
+            end_line: line,
+            column,
+        });
+        self.layout.spans.len() - 1
+    }
+
+    fn end_span(&mut self, span: usize) {
+        self.layout.spans[span].end_line = self.previous().line;
     }
 
     fn expression(&mut self) -> Result<Expr> {
@@ -66,11 +261,19 @@ impl<'a> Parser<'a> {
             stmts.push(self.declaration()?)
         }
 
-        self.consume(RIGHT_BRACE, "Expect '}' after block.")?;
+        let brace = self.consume(RIGHT_BRACE, "Expect '}' after block.")?;
+        self.layout.block_ends.push((brace.line, brace.column));
         Ok(stmts)
     }
 
     fn statement(&mut self) -> Result<Stmt> {
+        let span = self.begin_span();
+        let stmt = self.statement_kind()?;
+        self.end_span(span);
+        Ok(stmt)
+    }
+
+    fn statement_kind(&mut self) -> Result<Stmt> {
         match self.peek().kind {
             PRINT => {
                 self.advance();
@@ -96,6 +299,26 @@ impl<'a> Parser<'a> {
                 self.advance();
                 self.return_statement()
             }
+            THROW => {
+                let line = self.advance().line;
+                let value = self.expression()?;
+                self.consume(SEMICOLON, "Expect ';' after thrown value.")?;
+                Ok(Stmt::Throw(value, line))
+            }
+            TRY => {
+                self.advance();
+                self.try_statement()
+            }
+            BREAK => {
+                let line = self.advance().line;
+                self.consume(SEMICOLON, "Expect ';' after 'break'.")?;
+                Ok(Stmt::Break(line))
+            }
+            CONTINUE => {
+                let line = self.advance().line;
+                self.consume(SEMICOLON, "Expect ';' after 'continue'.")?;
+                Ok(Stmt::Continue(line))
+            }
             _ => self.expression_statement(),
         }
     }
@@ -103,21 +326,28 @@ impl<'a> Parser<'a> {
     fn for_statement(&mut self) -> Result<Stmt> {
         self.consume(LEFT_PAREN, "Expect '(' after 'for'.")?;
 
-        let initializer = match self.peek().kind {
-            SEMICOLON => {
-                self.advance();
-                None
-            }
-            VAR => {
-                self.advance();
-                Some(self.var_declaration()?)
-            }
-            _ => Some(self.expression_statement()?),
+        let var = usize::from(self.check(VAR));
+        if let (Some(IDENTIFIER(_)), Some(IN)) = (self.peek_at(var), self.peek_at(var + 1)) {
+            self.idx += var;
+            return self.for_in_statement();
+        }
+
+        let initializer = if self._match(&[SEMICOLON]) {
+            None
+        } else {
+            let span = self.begin_span();
+            let stmt = if self._match(&[VAR]) {
+                self.var_declaration()?
+            } else {
+                self.expression_statement()?
+            };
+            self.end_span(span);
+            Some(stmt)
         };
 
         let condition = match self.peek().kind {
-            SEMICOLON => Expr::Literal(Lit::Bool(true)),
-            _ => self.expression()?,
+            SEMICOLON => None,
+            _ => Some(self.expression()?),
         };
 
         self.consume(SEMICOLON, "Expect ';' after loop condition.")?;
@@ -129,19 +359,34 @@ impl<'a> Parser<'a> {
 
         self.consume(RIGHT_PAREN, "Expect ')' after for clauses.")?;
 
-        let mut body = self.statement()?;
+        Ok(Stmt::For {
+            initializer: initializer.map(Box::new),
+            condition,
+            increment,
+            body: Box::new(self.statement()?),
+        })
+    }
 
-        if let Some(expr) = increment {
-            body = Stmt::Block(vec![body, Stmt::Expr(expr)])
-        }
+    fn for_in_statement(&mut self) -> Result<Stmt> {
+        let name = self.consume_ident("Expect variable name.")?;
+        self.consume(IN, "Expect 'in' after loop variable.")?;
 
-        body = Stmt::While(condition, Box::new(body));
+        let iterable = self.expression()?;
+        let range_end = if self._match(&[DOT_DOT]) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
 
-        if let Some(expr) = initializer {
-            body = Stmt::Block(vec![expr, body])
-        }
+        let paren = self.consume(RIGHT_PAREN, "Expect ')' after for clauses.")?;
 
-        Ok(body)
+        Ok(Stmt::ForIn {
+            name: name.to_string(),
+            iterable,
+            range_end,
+            body: Box::new(self.statement()?),
+            line: paren.line,
+        })
     }
 
     fn if_statement(&mut self) -> Result<Stmt> {
@@ -180,6 +425,89 @@ impl<'a> Parser<'a> {
         Ok(Stmt::Return(value, self.peek().line))
     }
 
+    fn try_statement(&mut self) -> Result<Stmt> {
+        self.consume(LEFT_BRACE, "Expect '{' after 'try'.")?;
+        let body = self.block()?;
+
+        let catch = if self._match(&[CATCH]) {
+            self.consume(LEFT_PAREN, "Expect '(' after 'catch'.")?;
+            let name = self.consume_ident("Expect error variable name.")?;
+            self.consume(RIGHT_PAREN, "Expect ')' after error variable.")?;
+            self.consume(LEFT_BRACE, "Expect '{' before catch body.")?;
+            Some((name.to_string(), self.block()?))
+        } else {
+            None
+        };
+
+        let finally = if self._match(&[FINALLY]) {
+            self.consume(LEFT_BRACE, "Expect '{' after 'finally'.")?;
+            Some(self.block()?)
+        } else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            return Err(
+                self.parse_error(self.peek(), "Expect 'catch' or 'finally' after try block.")
+            );
+        }
+
+        Ok(Stmt::Try {
+            body,
+            catch,
+            finally,
+        })
+    }
+
+    fn import_declaration(&mut self) -> Result<Stmt> {
+        let line = self.previous().line;
+        let path = self.module_path()?;
+        self.consume(AS, "Expect 'as' after module path.")?;
+        let alias = self.consume_ident("Expect module name after 'as'.")?;
+        self.consume(SEMICOLON, "Expect ';' after import.")?;
+
+        Ok(Stmt::Import {
+            path,
+            alias: Some(alias.to_string()),
+            names: vec![],
+            line,
+        })
+    }
+
+    fn selective_import_declaration(&mut self) -> Result<Stmt> {
+        let line = self.previous().line;
+        let path = self.module_path()?;
+        self.consume(IMPORT, "Expect 'import' after module path.")?;
+
+        let mut names = vec![];
+        loop {
+            names.push(self.consume_ident("Expect name to import.")?.to_string());
+
+            if !self._match(&[COMMA]) {
+                break;
+            }
+        }
+
+        self.consume(SEMICOLON, "Expect ';' after import.")?;
+
+        Ok(Stmt::Import {
+            path,
+            alias: None,
+            names,
+            line,
+        })
+    }
+
+    fn module_path(&mut self) -> Result<String> {
+        match self.peek().kind {
+            STRING(path) => {
+                self.advance();
+                Ok(path.into_owned())
+            }
+            _ => Err(self.parse_error(self.peek(), "Expect module path string.")),
+        }
+    }
+
     fn var_declaration(&mut self) -> Result<Stmt> {
         let name = self.consume_ident("Expect variable name.")?;
 
@@ -199,7 +527,10 @@ impl<'a> Parser<'a> {
         self.consume(RIGHT_PAREN, "Expert '(' after 'while'.")?;
         let body = self.statement()?;
 
-        Ok(Stmt::While(condition, Box::new(body)))
+        Ok(Stmt::While {
+            condition,
+            body: Box::new(body),
+        })
     }
 
     fn expression_statement(&mut self) -> Result<Stmt> {
@@ -208,34 +539,11 @@ impl<'a> Parser<'a> {
         Ok(Stmt::Expr(expr))
     }
 
-    fn function(&mut self, kind: &str) -> Result<Stmt> {
+    fn function(&mut self, kind: &str, doc: Option<String>) -> Result<Stmt> {
         if let IDENTIFIER(name) = self.peek().kind {
             self.advance();
             self.consume(LEFT_PAREN, &format!("Expect '(' after {} name.", kind))?;
-            let mut params = vec![];
-
-            if !self.check(RIGHT_PAREN) {
-                loop {
-                    if params.len() >= Self::MAX_FN_ARGUMENT_COUNT {
-                        return Err(
-                            self.parse_error(self.peek(), "Can't have more than 255 parameters.")
-                        );
-                    }
-
-                    if let IDENTIFIER(name) = self.peek().kind {
-                        self.advance();
-                        params.push(name.into());
-                    } else {
-                        return Err(self.parse_error(self.peek(), "Expect parameter name."));
-                    }
-
-                    if !self._match(&[COMMA]) {
-                        break;
-                    }
-                }
-            }
-
-            self.consume(RIGHT_PAREN, "Expect ')' after parameters.")?;
+            let params = self.parameters()?;
             self.consume(LEFT_BRACE, &format!("Expect '{{' before {} body.", kind))?;
 
             Ok(Stmt::Function {
@@ -243,12 +551,85 @@ impl<'a> Parser<'a> {
                 params,
                 body: self.block()?,
                 line: self.peek().line,
+                doc,
             })
         } else {
             Err(self.parse_error(self.peek(), &format!("Expect {} name.", kind)))
         }
     }
 
+    fn parameters(&mut self) -> Result<Vec<String>> {
+        let mut params = vec![];
+
+        if !self.check(RIGHT_PAREN) {
+            loop {
+                if params.len() >= Self::MAX_FN_ARGUMENT_COUNT {
+                    return Err(
+                        self.parse_error(self.peek(), "Can't have more than 255 parameters.")
+                    );
+                }
+
+                if let IDENTIFIER(name) = self.peek().kind {
+                    self.advance();
+                    params.push(name.into());
+                } else {
+                    return Err(self.parse_error(self.peek(), "Expect parameter name."));
+                }
+
+                if !self._match(&[COMMA]) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(RIGHT_PAREN, "Expect ')' after parameters.")?;
+        Ok(params)
+    }
+
+    // `fun (a, b) { ... }`
+    fn lambda(&mut self) -> Result<Expr> {
+        let line = self.previous().line;
+        self.consume(LEFT_PAREN, "Expect '(' after 'fun'.")?;
+        let params = self.parameters()?;
+        self.consume(LEFT_BRACE, "Expect '{' before function body.")?;
+
+        Ok(Expr::Lambda {
+            params,
+            body: self.block()?,
+            line,
+            arrow: false,
+        })
+    }
+
+    // `(a, b) => expr`, entered just past the opening parenthesis.
+    fn arrow_function(&mut self) -> Result<Expr> {
+        let params = self.parameters()?;
+        let arrow = self.consume(ARROW, "Expect '=>' after parameters.")?;
+        let body = self.expression()?;
+
+        Ok(Expr::Lambda {
+            params,
+            body: vec![Stmt::Return(Some(body), arrow.line)],
+            line: arrow.line,
+            arrow: true,
+        })
+    }
+
+    // Whether the tokens after an opening parenthesis are an arrow
+    // function's parameter list rather than a grouping.
+    fn at_arrow_params(&self) -> bool {
+        let mut offset = 0;
+
+        loop {
+            match (self.peek_at(offset), self.peek_at(offset + 1)) {
+                (Some(RIGHT_PAREN), next) => return next == Some(ARROW),
+                (Some(IDENTIFIER(_)), Some(COMMA)) => offset += 2,
+                (Some(IDENTIFIER(_)), Some(RIGHT_PAREN)) => offset += 1,
+                _ => return false,
+            }
+        }
+    }
+
     fn assignment(&mut self) -> Result<Expr> {
         let expr = self.or()?;
 
@@ -258,6 +639,12 @@ impl<'a> Parser<'a> {
 
             return match expr {
                 Expr::Variable(name, ..) => Ok(Expr::assign(name, val, self.peek().line)),
+                Expr::Index {
+                    object,
+                    index,
+                    line,
+                } => Ok(Expr::set_index(*object, *index, val, line)),
+                Expr::Get { object, name, line } => Ok(Expr::set(*object, name, val, line)),
                 _ => Err(self.parse_error(tok, "Invalid assignment target.")),
             };
         }
@@ -353,6 +740,13 @@ impl<'a> Parser<'a> {
         loop {
             if self._match(&[LEFT_PAREN]) {
                 expr = self.finish_call(expr)?;
+            } else if self._match(&[DOT]) {
+                let name = self.consume_ident("Expect property name after '.'.")?;
+                expr = Expr::get(expr, name.to_string(), name.line);
+            } else if self._match(&[LEFT_BRACKET]) {
+                let index = self.expression()?;
+                let bracket = self.consume(RIGHT_BRACKET, "Expect ']' after index.")?;
+                expr = Expr::index(expr, index, bracket.line);
             } else {
                 break;
             }
@@ -384,22 +778,156 @@ impl<'a> Parser<'a> {
     }
 
     fn primary(&mut self) -> Result<Expr> {
+        // `advance` at the end gives back the last token again, which could
+        // be the one that led here.
+        if self.at_end() {
+            return Err(self.parse_error(self.peek(), "Expect expression."));
+        }
+
         match self.advance().kind {
             FALSE => Ok(Expr::Literal(Lit::Bool(false))),
             TRUE => Ok(Expr::Literal(Lit::Bool(true))),
             NIL => Ok(Expr::Literal(Lit::Nil)),
-            NUMBER(n) => Ok(Expr::Literal(Lit::Num(n))),
-            STRING(s) => Ok(Expr::Literal(Lit::Str(s.to_string()))),
+            NUMBER(n) => {
+                self.spelling();
+                Ok(Expr::Literal(Lit::Num(n)))
+            }
+            STRING(s) => {
+                self.spelling();
+                Ok(Expr::Literal(Lit::Str(s.to_string())))
+            }
+            INTERPOLATION(s) => self.interpolation(s),
+            // Named functions are declarations, which aren't allowed here.
+            FUN if !matches!(self.peek().kind, IDENTIFIER(_)) => self.lambda(),
+            LEFT_PAREN if self.at_arrow_params() => self.arrow_function(),
             LEFT_PAREN => {
                 let expr = self.expression()?;
                 self.consume(RIGHT_PAREN, "Expect ')' after expression.")?;
                 Ok(Expr::grouping(expr))
             }
-            IDENTIFIER(s) => Ok(Expr::Variable(s.to_string(), self.peek().line)),
+            LEFT_BRACKET => Ok(Expr::List(self.list()?)),
+            // `{}` is left alone so that an empty block can't be mistaken for
+            // an expression; the empty map is spelled `{:}` instead.
+            LEFT_BRACE if !self.check(RIGHT_BRACE) => {
+                let entries = self.map()?;
+                Ok(Expr::Map(entries, self.previous().line))
+            }
+            IDENTIFIER(s) => Ok(Expr::Variable(s.to_string(), self.previous().line)),
             _ => Err(self.parse_error(self.previous(), "Expect expression.")),
         }
     }
 
+    fn list(&mut self) -> Result<Vec<Expr>> {
+        let mut elements = vec![];
+
+        while !self.check(RIGHT_BRACKET) {
+            elements.push(self.expression()?);
+
+            if !self._match(&[COMMA]) {
+                break;
+            }
+        }
+
+        self.consume(RIGHT_BRACKET, "Expect ']' after list elements.")?;
+        Ok(elements)
+    }
+
+    fn map(&mut self) -> Result<Vec<(Expr, Expr)>> {
+        let mut entries = vec![];
+
+        if self._match(&[COLON]) {
+            self.consume(RIGHT_BRACE, "Expect '}' after ':' in empty map.")?;
+            return Ok(entries);
+        }
+
+        loop {
+            let key = self.expression()?;
+            self.consume(COLON, "Expect ':' after map key.")?;
+            entries.push((key, self.expression()?));
+
+            if !self._match(&[COMMA]) {
+                break;
+            }
+        }
+
+        self.consume(RIGHT_BRACE, "Expect '}' after map entries.")?;
+        Ok(entries)
+    }
+
+    // Desugars `"a ${b} c"` into `"a " + str(b) + " c"`.
+    fn interpolation(&mut self, head: Cow<'a, str>) -> Result<Expr> {
+        let mut segments = vec![];
+        // Recorded before any nested string, to keep them in source order.
+        let idx = self.layout.interpolations.len();
+        self.layout.interpolations.push(0);
+
+        if !head.is_empty() {
+            self.spelling();
+            segments.push(Expr::Literal(Lit::Str(head.into_owned())));
+        }
+
+        loop {
+            // The rest of the string comes straight after the `${`.
+            if self.peek().lexeme.starts_with('}') {
+                let brace = Token {
+                    kind: RIGHT_BRACE,
+                    ..self.peek()
+                };
+                return Err(self.parse_error(brace, "Expect expression."));
+            }
+
+            let line = self.previous().line;
+            let expr = self.expression()?;
+            segments.push(Expr::unary(UnOp::Str, expr, line));
+
+            let tail = match self.peek().kind {
+                INTERPOLATION(s) => s,
+                STRING(s) => s,
+                _ => return Err(self.parse_error(self.peek(), "Expect '}' after interpolation.")),
+            };
+
+            let token = self.advance();
+            if !tail.is_empty() {
+                self.spelling();
+                segments.push(Expr::Literal(Lit::Str(tail.into_owned())));
+            }
+
+            if let STRING(_) = token.kind {
+                break;
+            }
+        }
+
+        self.layout.interpolations[idx] = segments.len();
+
+        let line = self.previous().line;
+        let mut segments = segments.into_iter();
+        let first = segments.next().unwrap();
+
+        Ok(segments.fold(first, |lhs, rhs| Expr::binary(lhs, BinOp::Add, rhs, line)))
+    }
+
+    // Records how the literal just consumed was written, leaving out the
+    // `"`, `}` and `${` around a piece of string.
+    fn spelling(&mut self) {
+        let token = self.previous();
+        let text = match token.kind {
+            NUMBER(_) => token.lexeme,
+            _ => {
+                let text = &token.lexeme[1..];
+                match token.kind {
+                    INTERPOLATION(_) => &text[..text.len() - 2],
+                    _ => &text[..text.len() - 1],
+                }
+            }
+        };
+        let line = token.line - token.lexeme.matches('\n').count() as u64;
+        self.layout.literals.push(Literal {
+            text: text.into(),
+            line,
+            column: token.column,
+        });
+    }
+
     fn synchronize(&mut self) {
         self.advance();
         while !self.at_end() {
@@ -408,7 +936,8 @@ impl<'a> Parser<'a> {
             }
 
             match self.peek().kind {
-                CLASS | FUN | VAR | FOR | IF | WHILE | PRINT | RETURN => return,
+                CLASS | FUN | VAR | FOR | IF | WHILE | PRINT | RETURN | THROW | TRY | IMPORT
+                | FROM => return,
                 _ => {
                     self.advance();
                 }
@@ -418,7 +947,7 @@ impl<'a> Parser<'a> {
 
     fn _match(&mut self, kinds: &[TokenKind<'a>]) -> bool {
         for kind in kinds {
-            if self.check(*kind) {
+            if self.check(kind.clone()) {
                 self.advance();
                 return true;
             }
@@ -447,11 +976,16 @@ impl<'a> Parser<'a> {
     }
 
     fn peek(&self) -> Token<'a> {
-        self.tokens[self.idx]
+        self.tokens[self.idx].clone()
+    }
+
+    // Looks `offset` tokens past the current one.
+    fn peek_at(&self, offset: usize) -> Option<TokenKind<'a>> {
+        self.tokens.get(self.idx + offset).map(|t| t.kind.clone())
     }
 
     fn previous(&self) -> Token<'a> {
-        self.tokens[self.idx - 1]
+        self.tokens[self.idx - 1].clone()
     }
 
     fn consume(&mut self, kind: TokenKind<'a>, msg: &str) -> Result<Token<'a>> {
@@ -481,3 +1015,35 @@ impl<'a> Parser<'a> {
         LoxError::Compile(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(source: &str) -> Option<String> {
+        match parse(source).0.pop() {
+            Some(Stmt::Function { doc, .. }) => doc,
+            stmt => panic!("expected a function, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn doc_comments_attach_to_the_next_function() {
+        assert_eq!(
+            doc("/// Adds.\n/// Twice.\nfun f() {}").as_deref(),
+            Some("Adds.\nTwice.")
+        );
+        assert_eq!(doc("////\nfun f() {}"), None);
+    }
+
+    #[test]
+    fn doc_comments_stop_at_a_blank_line_or_comment() {
+        assert_eq!(doc("/// Lost.\n\nfun f() {}"), None);
+        assert_eq!(doc("/// Lost.\n// Note.\nfun f() {}"), None);
+        assert_eq!(doc("/// Lost.\n/* Note. */\nfun f() {}"), None);
+        assert_eq!(
+            doc("/// Lost.\n\n/// Kept.\nfun f() {}").as_deref(),
+            Some("Kept.")
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::time::SystemTime;
+
+/// Where the interpreter gets the time and random numbers from. Swapping in
+/// a `DeterministicHost` makes `clock()` and `random()` reproducible, for
+/// golden-output tests and replays. Map iteration needs no hook: maps
+/// always iterate in insertion order.
+pub trait Host {
+    /// Seconds since some fixed point, as returned by `clock()`.
+    fn clock(&mut self) -> f64;
+
+    /// A number in `[0, 1)`, as returned by `random()`.
+    fn random(&mut self) -> f64;
+
+    /// Restarts the random sequence, as `seed(n)` does.
+    fn seed(&mut self, seed: u64);
+}
+
+/// The default host: wall-clock time and a generator seeded from it.
+#[derive(Debug)]
+pub struct SystemHost {
+    rng: Rng,
+}
+
+impl SystemHost {
+    pub fn new() -> Self {
+        SystemHost {
+            rng: Rng::new(now().to_bits()),
+        }
+    }
+}
+
+impl Default for SystemHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Host for SystemHost {
+    fn clock(&mut self) -> f64 {
+        now()
+    }
+
+    fn random(&mut self) -> f64 {
+        self.rng.next_f64()
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+}
+
+/// A host whose clock starts at zero and moves forward one millisecond
+/// each time it is read, and whose generator starts from a fixed seed.
+#[derive(Debug)]
+pub struct DeterministicHost {
+    // Milliseconds; counting in floats would drift
+    ticks: u64,
+    rng: Rng,
+}
+
+impl DeterministicHost {
+    pub fn new(seed: u64) -> Self {
+        DeterministicHost {
+            ticks: 0,
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Default for DeterministicHost {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Host for DeterministicHost {
+    fn clock(&mut self) -> f64 {
+        let time = self.ticks as f64 / 1000.0;
+        self.ticks += 1;
+        time
+    }
+
+    fn random(&mut self) -> f64 {
+        self.rng.next_f64()
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+}
+
+fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("There is no time")
+        .as_secs_f64()
+}
+
+/// A small SplitMix64 generator. Good enough for scripts, and reproducible
+/// once seeded.
+#[derive(Debug)]
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::ast::{BinOp, Expr, Keyword, Lit, UnOp};
+use crate::ast::{BinOp, Expr, Keyword, Lit, Stmt, UnOp};
 use crate::visitor::ExprVisitor;
 
 pub struct AstPrinter;
@@ -27,8 +27,31 @@ impl ExprVisitor for AstPrinter {
     fn visit_expr(&mut self, e: &Expr) -> String {
         match e {
             Expr::Binary { rhs, lhs, op, line } => self.visit_binary_expr(lhs, *op, rhs, *line),
-            Expr::Call { .. } => todo!(),
+            Expr::Call { callee, args, line } => self.visit_call_expr(callee, args, *line),
+            Expr::Get { object, name, line } => self.visit_get_expr(object, name, *line),
             Expr::Grouping(expr) => self.visit_grouping_expr(expr),
+            Expr::Index {
+                object,
+                index,
+                line,
+            } => self.visit_index_expr(object, index, *line),
+            Expr::Lambda {
+                params, body, line, ..
+            } => self.visit_lambda_expr(params, body, *line),
+            Expr::List(elements) => self.visit_list_expr(elements),
+            Expr::Map(entries, line) => self.visit_map_expr(entries, *line),
+            Expr::Set {
+                object,
+                name,
+                value,
+                line,
+            } => self.visit_set_expr(object, name, value, *line),
+            Expr::SetIndex {
+                object,
+                index,
+                value,
+                line,
+            } => self.visit_set_index_expr(object, index, value, *line),
             Expr::Unary(op, expr, line) => self.visit_unary_expr(*op, expr, *line),
             Expr::Literal(lit) => self.visit_literal_expr(lit),
             Expr::Logical { rhs, lhs, kw, line } => self.visit_logical_expr(lhs, *kw, rhs, *line),
@@ -45,8 +68,47 @@ impl ExprVisitor for AstPrinter {
         self.parenthesize(&op, &[lhs, rhs])
     }
 
-    fn visit_call_expr(&mut self, _callee: &Expr, _args: &[Expr], _: u64) -> Self::Output {
-        todo!()
+    fn visit_call_expr(&mut self, callee: &Expr, args: &[Expr], _: u64) -> Self::Output {
+        let exprs = std::iter::once(callee).chain(args).collect::<Vec<_>>();
+        self.parenthesize(&"call", &exprs)
+    }
+
+    fn visit_get_expr(&mut self, object: &Expr, name: &str, _: u64) -> Self::Output {
+        self.parenthesize(&format!(". {}", name), &[object])
+    }
+
+    fn visit_index_expr(&mut self, object: &Expr, index: &Expr, _: u64) -> Self::Output {
+        self.parenthesize(&"[]", &[object, index])
+    }
+
+    fn visit_lambda_expr(&mut self, params: &[String], _: &[Stmt], _: u64) -> Self::Output {
+        format!("(fun ({}))", params.join(" "))
+    }
+
+    fn visit_list_expr(&mut self, elements: &[Expr]) -> Self::Output {
+        self.parenthesize(&"list", &elements.iter().collect::<Vec<_>>())
+    }
+
+    fn visit_map_expr(&mut self, entries: &[(Expr, Expr)], _: u64) -> Self::Output {
+        let exprs = entries
+            .iter()
+            .flat_map(|(k, v)| vec![k, v])
+            .collect::<Vec<_>>();
+        self.parenthesize(&"map", &exprs)
+    }
+
+    fn visit_set_expr(&mut self, object: &Expr, name: &str, value: &Expr, _: u64) -> Self::Output {
+        self.parenthesize(&format!(".= {}", name), &[object, value])
+    }
+
+    fn visit_set_index_expr(
+        &mut self,
+        object: &Expr,
+        index: &Expr,
+        value: &Expr,
+        _: u64,
+    ) -> Self::Output {
+        self.parenthesize(&"[]=", &[object, index, value])
     }
 
     fn visit_literal_expr(&mut self, literal: &Lit) -> Self::Output {
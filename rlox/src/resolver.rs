@@ -6,14 +6,16 @@ use std::collections::HashMap;
 pub struct Resolver<'a> {
     interpreter: &'a mut Interpreter,
     scopes: Vec<HashMap<String, bool>>,
+    // Number of loops enclosing the current statement in this function.
+    loop_depth: usize,
 }
 
 impl<'a> Resolver<'a> {
-    #[allow(unused)]
-    pub fn new(interpreter: &mut Interpreter) -> Resolver {
+    pub fn new(interpreter: &mut Interpreter) -> Resolver<'_> {
         Resolver {
             interpreter,
             scopes: Vec::new(),
+            loop_depth: 0,
         }
     }
 
@@ -38,6 +40,7 @@ impl<'a> Resolver<'a> {
     }
 
     fn resolve_function(&mut self, params: &[String], body: &[Stmt]) -> Result<(), LoxError> {
+        let enclosing_loops = std::mem::take(&mut self.loop_depth);
         self.begin_scope();
 
         for param in params {
@@ -45,10 +48,35 @@ impl<'a> Resolver<'a> {
             self.define(param);
         }
 
-        self.resolve(body)?;
+        let res = self.resolve(body);
         self.end_scope();
+        self.loop_depth = enclosing_loops;
 
-        Ok(())
+        res
+    }
+
+    fn resolve_for(
+        &mut self,
+        initializer: Option<&Stmt>,
+        condition: Option<&Expr>,
+        increment: Option<&Expr>,
+        body: &Stmt,
+    ) -> Result<(), LoxError> {
+        if let Some(stmt) = initializer {
+            self.resolve_stmt(stmt)?;
+        }
+        if let Some(expr) = condition {
+            self.resolve_expr(expr)?;
+        }
+        if let Some(expr) = increment {
+            self.resolve_expr(expr)?;
+        }
+
+        self.loop_depth += 1;
+        let res = self.resolve_stmt(body);
+        self.loop_depth -= 1;
+
+        res
     }
 
     fn end_scope(&mut self) {
@@ -66,6 +94,18 @@ impl<'a> Resolver<'a> {
         Ok(())
     }
 
+    fn check_in_loop(&self, keyword: &str, line: u64) -> Result<(), LoxError> {
+        if self.loop_depth == 0 {
+            let msg = format!(
+                "[line {}] Error at '{}': Can't use '{}' outside of a loop.",
+                line, keyword, keyword
+            );
+            return Err(LoxError::Compile(msg));
+        }
+
+        Ok(())
+    }
+
     fn declare(&mut self, name: &str) {
         if let Some(scope) = self.scopes.last_mut() {
             scope.insert(name.into(), false);
@@ -90,10 +130,57 @@ impl StmtVisitor for Resolver<'_> {
         Ok(())
     }
 
+    fn visit_break_stmt(&mut self, line: u64) -> Self::Output {
+        self.check_in_loop("break", line)
+    }
+
+    fn visit_continue_stmt(&mut self, line: u64) -> Self::Output {
+        self.check_in_loop("continue", line)
+    }
+
     fn visit_expression_stmt(&mut self, expr: &Expr) -> Self::Output {
         self.resolve_expr(expr)
     }
 
+    fn visit_for_stmt(
+        &mut self,
+        initializer: Option<&Stmt>,
+        condition: Option<&Expr>,
+        increment: Option<&Expr>,
+        body: &Stmt,
+    ) -> Self::Output {
+        self.begin_scope();
+        let res = self.resolve_for(initializer, condition, increment, body);
+        self.end_scope();
+
+        res
+    }
+
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &str,
+        iterable: &Expr,
+        range_end: Option<&Expr>,
+        body: &Stmt,
+        _line: u64,
+    ) -> Self::Output {
+        self.resolve_expr(iterable)?;
+        if let Some(end) = range_end {
+            self.resolve_expr(end)?;
+        }
+
+        self.begin_scope();
+        self.declare(name);
+        self.define(name);
+
+        self.loop_depth += 1;
+        let res = self.resolve_stmt(body);
+        self.loop_depth -= 1;
+        self.end_scope();
+
+        res
+    }
+
     fn visit_function_stmt(
         &mut self,
         name: &str,
@@ -121,6 +208,21 @@ impl StmtVisitor for Resolver<'_> {
         Ok(())
     }
 
+    fn visit_import_stmt(
+        &mut self,
+        _path: &str,
+        alias: Option<&str>,
+        names: &[String],
+        _line: u64,
+    ) -> Self::Output {
+        for name in alias.into_iter().chain(names.iter().map(String::as_str)) {
+            self.declare(name);
+            self.define(name);
+        }
+
+        Ok(())
+    }
+
     fn visit_print_stmt(&mut self, expr: &Expr) -> Self::Output {
         self.resolve_expr(expr)
     }
@@ -133,6 +235,34 @@ impl StmtVisitor for Resolver<'_> {
         Ok(())
     }
 
+    fn visit_throw_stmt(&mut self, expr: &Expr, _: u64) -> Self::Output {
+        self.resolve_expr(expr)
+    }
+
+    fn visit_try_stmt(
+        &mut self,
+        body: &[Stmt],
+        catch: Option<(&str, &[Stmt])>,
+        finally: Option<&[Stmt]>,
+    ) -> Self::Output {
+        self.visit_block_stmt(body)?;
+
+        if let Some((name, handler)) = catch {
+            self.begin_scope();
+            self.declare(name);
+            self.define(name);
+            let res = self.resolve(handler);
+            self.end_scope();
+            res?;
+        }
+
+        if let Some(stmts) = finally {
+            self.visit_block_stmt(stmts)?;
+        }
+
+        Ok(())
+    }
+
     fn visit_var_stmt(&mut self, name: &str, initializer: Option<&Expr>) -> Self::Output {
         self.declare(name);
         if let Some(expr) = initializer {
@@ -145,7 +275,12 @@ impl StmtVisitor for Resolver<'_> {
 
     fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> Self::Output {
         self.resolve_expr(condition)?;
-        self.resolve_stmt(body)
+
+        self.loop_depth += 1;
+        let res = self.resolve_stmt(body);
+        self.loop_depth -= 1;
+
+        res
     }
 }
 
@@ -170,6 +305,34 @@ impl ExprVisitor for Resolver<'_> {
         Ok(())
     }
 
+    fn visit_get_expr(&mut self, object: &Expr, _: &str, _: u64) -> Self::Output {
+        self.resolve_expr(object)
+    }
+
+    fn visit_index_expr(&mut self, object: &Expr, index: &Expr, _: u64) -> Self::Output {
+        self.resolve_expr(object)?;
+        self.resolve_expr(index)
+    }
+
+    fn visit_lambda_expr(&mut self, params: &[String], body: &[Stmt], _: u64) -> Self::Output {
+        self.resolve_function(params, body)
+    }
+
+    fn visit_list_expr(&mut self, elements: &[Expr]) -> Self::Output {
+        for element in elements {
+            self.resolve_expr(element)?;
+        }
+        Ok(())
+    }
+
+    fn visit_map_expr(&mut self, entries: &[(Expr, Expr)], _: u64) -> Self::Output {
+        for (key, value) in entries {
+            self.resolve_expr(key)?;
+            self.resolve_expr(value)?;
+        }
+        Ok(())
+    }
+
     fn visit_literal_expr(&mut self, _: &Lit) -> Self::Output {
         Ok(())
     }
@@ -179,14 +342,34 @@ impl ExprVisitor for Resolver<'_> {
         self.resolve_expr(rhs)
     }
 
+    fn visit_set_expr(&mut self, object: &Expr, _: &str, value: &Expr, _: u64) -> Self::Output {
+        self.resolve_expr(value)?;
+        self.resolve_expr(object)
+    }
+
+    fn visit_set_index_expr(
+        &mut self,
+        object: &Expr,
+        index: &Expr,
+        value: &Expr,
+        _: u64,
+    ) -> Self::Output {
+        self.resolve_expr(value)?;
+        self.resolve_expr(object)?;
+        self.resolve_expr(index)
+    }
+
     fn visit_unary_expr(&mut self, _: UnOp, rhs: &Expr, _: u64) -> Self::Output {
         self.resolve_expr(rhs)
     }
 
-    fn visit_variable_expr(&mut self, name: &str, _: u64) -> Self::Output {
+    fn visit_variable_expr(&mut self, name: &str, line: u64) -> Self::Output {
         if let Some(false) = self.scopes.last().and_then(|scope| scope.get(name)) {
-            let msg = "Can't read variable in its own initializer.";
-            return Err(LoxError::Compile(msg.into()));
+            let msg = format!(
+                "[line {}] Error at '{}': Can't read local variable in its own initializer.",
+                line, name
+            );
+            return Err(LoxError::Compile(msg));
         }
 
         self.resolve_local(name)
@@ -1,6 +1,9 @@
+use std::borrow::Cow;
 use std::fmt;
 use std::str::{Chars, FromStr};
 
+use unicode_xid::UnicodeXID;
+
 use crate::token::{Token, TokenKind};
 use TokenKind::*;
 
@@ -8,6 +11,11 @@ use TokenKind::*;
 pub enum ScanError {
     UnexpectedChar(char),
     UnterminatedString,
+    InvalidEscape(char),
+    InvalidUnicodeEscape,
+    UnterminatedInterpolation,
+    InvalidNumber,
+    UnterminatedComment,
 }
 
 impl fmt::Display for ScanError {
@@ -15,28 +23,119 @@ impl fmt::Display for ScanError {
         match self {
             ScanError::UnexpectedChar(_) => write!(f, "Unexpected character."),
             ScanError::UnterminatedString => write!(f, "Unterminated string."),
+            ScanError::InvalidEscape(c) => write!(f, "Invalid escape sequence '\\{}'.", c),
+            ScanError::InvalidUnicodeEscape => write!(f, "Invalid unicode escape sequence."),
+            ScanError::UnterminatedInterpolation => write!(f, "Unterminated interpolation."),
+            ScanError::InvalidNumber => write!(f, "Invalid number literal."),
+            ScanError::UnterminatedComment => write!(f, "Unterminated block comment."),
+        }
+    }
+}
+
+/// Whether `src` stops partway through something: a bracket left open, or
+/// a string or block comment that runs to the end. More input might make
+/// it valid, so the REPL keeps reading rather than reporting an error.
+pub fn is_incomplete(src: &str) -> bool {
+    let mut depth = 0i64;
+
+    for token in tokenize(src) {
+        match token.kind {
+            LEFT_PAREN | LEFT_BRACE | LEFT_BRACKET => depth += 1,
+            RIGHT_PAREN | RIGHT_BRACE | RIGHT_BRACKET => depth -= 1,
+            ERROR(
+                ScanError::UnterminatedString
+                | ScanError::UnterminatedInterpolation
+                | ScanError::UnterminatedComment,
+            ) => return true,
+            _ => {}
         }
     }
+
+    depth > 0
 }
 
 pub fn tokenize(mut src: &str) -> impl Iterator<Item = Token<'_>> {
     let mut start_line = 1;
+    let mut start_column = 1;
     let mut at_end = false;
+    // Brace depth of each `${` we are currently inside of.
+    let mut interpolations: Vec<usize> = vec![];
+    // Where the string holding the outermost open `${` starts.
+    let mut opened = (0, 0);
+    // A string that had a bad escape in it, to go after the error.
+    let mut pending = None;
 
     std::iter::from_fn(move || {
+        if let Some(token) = pending.take() {
+            return Some(token);
+        }
+
         if at_end {
             return None;
         }
 
         if src.is_empty() {
+            if !interpolations.is_empty() {
+                interpolations.clear();
+                let kind = ERROR(ScanError::UnterminatedInterpolation);
+                return Some(Token::new(kind, opened.0, opened.1));
+            }
+
             at_end = true;
-            return Some(Token::new(EOF, start_line));
+            return Some(Token::new(EOF, start_line, start_column));
         }
 
-        let (token, consumed) = Scanner::new(src, start_line).scan();
+        let mut scanner = Scanner::new(src, start_line, start_column);
+
+        let (token, consumed) = match interpolations.last_mut() {
+            Some(0) if src.starts_with('}') => {
+                let (token, consumed) = scanner.string_continuation();
+                if !matches!(token.kind, INTERPOLATION(_)) {
+                    interpolations.pop();
+                }
+                (token, consumed)
+            }
+            depth => {
+                let (token, consumed) = scanner.scan();
+                match (&token.kind, depth) {
+                    (INTERPOLATION(_), None) => {
+                        opened = (token.line, token.column);
+                        interpolations.push(0);
+                    }
+                    (INTERPOLATION(_), _) => interpolations.push(0),
+                    (LEFT_BRACE, Some(depth)) => *depth += 1,
+                    (RIGHT_BRACE, Some(depth)) => *depth -= 1,
+                    _ => {}
+                }
+                (token, consumed)
+            }
+        };
+
+        let token = Token {
+            lexeme: &src[..consumed],
+            ..token
+        };
+
         start_line = token.line;
+        start_column = scanner.column;
         src = &src[consumed..];
 
+        // A `"` left open inside `${` means it was the `${` that never got
+        // closed, and that is the one mistake worth reporting.
+        if token.kind == ERROR(ScanError::UnterminatedString) && !interpolations.is_empty() {
+            interpolations.clear();
+            let kind = ERROR(ScanError::UnterminatedInterpolation);
+            return Some(Token::new(kind, opened.0, opened.1));
+        }
+
+        // The string itself is still good, so the parser gets it too and
+        // doesn't report a second error where it's missing.
+        if let (Some(e), STRING(_) | INTERPOLATION(_)) = (scanner.escape_error, &token.kind) {
+            let error = Token::new(ERROR(e), token.line, token.column);
+            pending = Some(token);
+            return Some(error);
+        }
+
         Some(token)
     })
     .filter(|t| !t.is_whitespace())
@@ -46,14 +145,21 @@ struct Scanner<'a> {
     src: &'a str,
     chars: Chars<'a>,
     line: u64,
+    column: u64,
+    start_column: u64,
+    // The first bad escape sequence in the string just scanned.
+    escape_error: Option<ScanError>,
 }
 
 impl<'a> Scanner<'a> {
-    fn new(src: &'a str, line: u64) -> Scanner<'a> {
+    fn new(src: &'a str, line: u64, column: u64) -> Scanner<'a> {
         Scanner {
             src,
             chars: src.chars(),
             line,
+            column,
+            start_column: column,
+            escape_error: None,
         }
     }
 
@@ -66,25 +172,37 @@ impl<'a> Scanner<'a> {
                 WHITESPACE
             }
             '/' => match self.peek() {
-                Some('/') => {
-                    self.advance_while(|c| c != '\n');
-                    COMMENT
-                }
+                Some('/') => self.line_comment(),
+                Some('*') => self.block_comment(),
                 _ => SLASH,
             },
             c if is_ident_start(c) => self.identifier(),
+            '0' if matches!(self.peek(), Some('x' | 'X' | 'b' | 'B')) => self.radix_number(),
             '0'..='9' => self.number(),
             '(' => LEFT_PAREN,
             ')' => RIGHT_PAREN,
             '{' => LEFT_BRACE,
             '}' => RIGHT_BRACE,
+            '[' => LEFT_BRACKET,
+            ']' => RIGHT_BRACKET,
             ',' => COMMA,
+            ':' => COLON,
             ';' => SEMICOLON,
-            '.' => DOT,
+            '.' => match self.peek() {
+                Some('.') => {
+                    self.advance();
+                    DOT_DOT
+                }
+                _ => DOT,
+            },
             '-' => MINUS,
             '+' => PLUS,
             '*' => STAR,
             '!' => self.maybe_double(BANG, BANG_EQUAL),
+            '=' if self.peek() == Some('>') => {
+                self.advance();
+                ARROW
+            }
             '=' => self.maybe_double(EQUAL, EQUAL_EQUAL),
             '>' => self.maybe_double(GREATER, GREATER_EQUAL),
             '<' => self.maybe_double(LESS, LESS_EQUAL),
@@ -92,37 +210,220 @@ impl<'a> Scanner<'a> {
             _ => ERROR(ScanError::UnexpectedChar(c)),
         };
 
-        (Token::new(kind, self.line), self.consumed())
+        (self.token(kind), self.consumed())
+    }
+
+    fn line_comment(&mut self) -> TokenKind<'a> {
+        self.advance_while(|c| c != '\n');
+        let text = &self.src[..self.consumed()];
+
+        // `///` starts a doc comment, but `////` is just a regular comment.
+        match text.strip_prefix("///") {
+            Some(doc) if !doc.starts_with('/') => DOC_COMMENT(doc.trim_end_matches('\r')),
+            _ => COMMENT(text.trim_end_matches('\r')),
+        }
+    }
+
+    fn block_comment(&mut self) -> TokenKind<'a> {
+        self.advance();
+        let mut depth = 1;
+
+        while depth > 0 {
+            match (self.advance(), self.peek()) {
+                (Some('/'), Some('*')) => {
+                    self.advance();
+                    depth += 1;
+                }
+                (Some('*'), Some('/')) => {
+                    self.advance();
+                    depth -= 1;
+                }
+                (None, _) => return ERROR(ScanError::UnterminatedComment),
+                _ => {}
+            }
+        }
+
+        COMMENT(&self.src[..self.consumed()])
     }
 
     fn identifier(&mut self) -> TokenKind<'a> {
-        self.advance_while(|c| c.is_alphanumeric() || c == '_');
+        self.advance_while(is_ident_continue);
         let src = &self.src[..self.consumed()];
         TokenKind::from_str(src).unwrap_or(IDENTIFIER(src))
     }
 
     fn number(&mut self) -> TokenKind<'a> {
-        self.advance_while(is_number);
+        self.advance_while(|c| is_number(c) || c == '_');
 
         if let (Some('.'), Some(c)) = (self.peek(), self.peek_next()) {
             if is_number(c) {
                 self.advance();
-                self.advance_while(is_number)
+                self.advance_while(|c| is_number(c) || c == '_');
+            }
+        }
+
+        if let Some('e' | 'E') = self.peek() {
+            self.advance();
+            if let Some('+' | '-') = self.peek() {
+                self.advance();
+            }
+            if !self.peek().is_some_and(is_number) {
+                return ERROR(ScanError::InvalidNumber);
             }
+            self.advance_while(|c| is_number(c) || c == '_');
         }
 
         let src = &self.src[..self.consumed()];
-        NUMBER(f64::from_str(src).unwrap())
+        if !valid_separators(src, u8::is_ascii_digit) {
+            return ERROR(ScanError::InvalidNumber);
+        }
+
+        match f64::from_str(&src.replace('_', "")) {
+            Ok(n) => NUMBER(n),
+            Err(_) => ERROR(ScanError::InvalidNumber),
+        }
+    }
+
+    // Hexadecimal (`0x1F`) and binary (`0b1010`) integer literals.
+    fn radix_number(&mut self) -> TokenKind<'a> {
+        let radix = match self.advance() {
+            Some('x' | 'X') => 16,
+            _ => 2,
+        };
+
+        self.advance_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        let digits = &self.src[2..self.consumed()];
+
+        if digits.is_empty() || !valid_separators(digits, u8::is_ascii_hexdigit) {
+            return ERROR(ScanError::InvalidNumber);
+        }
+
+        // Accumulate in a float so that long literals lose precision rather
+        // than overflow, the same as decimal ones do.
+        let mut n = 0.0;
+        for c in digits.chars().filter(|&c| c != '_') {
+            match c.to_digit(radix) {
+                Some(d) => n = n * radix as f64 + d as f64,
+                None => return ERROR(ScanError::InvalidNumber),
+            }
+        }
+
+        NUMBER(n)
     }
 
     fn string(&mut self) -> TokenKind<'a> {
-        while let Some(c) = self.advance() {
-            if c == '"' {
-                return STRING(&self.src[1..self.consumed() - 1]);
+        let start = self.consumed();
+        // Stays borrowed from the source until the first escape sequence.
+        let mut value = Cow::Borrowed("");
+
+        loop {
+            let end = self.consumed();
+
+            let segment = match self.advance() {
+                None => return ERROR(ScanError::UnterminatedString),
+                Some('"') => STRING,
+                Some('$') if self.peek() == Some('{') => {
+                    self.advance();
+                    INTERPOLATION
+                }
+                Some('\\') => {
+                    if let Cow::Borrowed(_) = value {
+                        value = Cow::Owned(self.src[start..end].to_string());
+                    }
+
+                    match self.escape() {
+                        Ok(c) => value.to_mut().push(c),
+                        Err(e) => {
+                            self.escape_error.get_or_insert(e);
+                        }
+                    }
+                    continue;
+                }
+                Some(c) => {
+                    if let Cow::Owned(s) = &mut value {
+                        s.push(c);
+                    }
+                    continue;
+                }
+            };
+
+            return match value {
+                Cow::Borrowed(_) => segment(Cow::Borrowed(&self.src[start..end])),
+                value => segment(value),
+            };
+        }
+    }
+
+    /// Scans the rest of an interpolated string, starting at the `}` that
+    /// closes the previous `${`.
+    fn string_continuation(&mut self) -> (Token<'a>, usize) {
+        self.advance();
+        let kind = self.string();
+        (self.token(kind), self.consumed())
+    }
+
+    fn token(&self, kind: TokenKind<'a>) -> Token<'a> {
+        Token::new(kind, self.line, self.start_column)
+    }
+
+    fn escape(&mut self) -> Result<char, ScanError> {
+        match self.peek() {
+            Some(c @ ('"' | '\\' | '$')) => {
+                self.advance();
+                Ok(c)
+            }
+            Some('n') => {
+                self.advance();
+                Ok('\n')
+            }
+            Some('t') => {
+                self.advance();
+                Ok('\t')
+            }
+            Some('r') => {
+                self.advance();
+                Ok('\r')
+            }
+            Some('0') => {
+                self.advance();
+                Ok('\0')
             }
+            Some('u') => {
+                self.advance();
+                self.unicode_escape()
+            }
+            Some(c) => {
+                self.advance();
+                Err(ScanError::InvalidEscape(c))
+            }
+            None => Err(ScanError::UnterminatedString),
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, ScanError> {
+        if self.peek() != Some('{') {
+            return Err(ScanError::InvalidUnicodeEscape);
+        }
+        self.advance();
+
+        let mut digits = String::new();
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_hexdigit() {
+                break;
+            }
+            digits.push(c);
+            self.advance();
+        }
+
+        if self.peek() != Some('}') || digits.is_empty() || digits.len() > 6 {
+            return Err(ScanError::InvalidUnicodeEscape);
         }
+        self.advance();
 
-        ERROR(ScanError::UnterminatedString)
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or(ScanError::InvalidUnicodeEscape)
     }
 
     fn maybe_double(&mut self, single: TokenKind<'a>, double: TokenKind<'a>) -> TokenKind<'a> {
@@ -135,11 +436,13 @@ impl<'a> Scanner<'a> {
     }
 
     fn advance(&mut self) -> Option<char> {
-        self.chars.next().map(|c| {
+        self.chars.next().inspect(|&c| {
             if c == '\n' {
                 self.line += 1;
-            };
-            c
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
         })
     }
 
@@ -166,9 +469,49 @@ impl<'a> Scanner<'a> {
 }
 
 fn is_ident_start(c: char) -> bool {
-    matches!(c, 'a'..='z' | 'A'..='Z' | '_')
+    c == '_' || UnicodeXID::is_xid_start(c)
+}
+
+fn is_ident_continue(c: char) -> bool {
+    UnicodeXID::is_xid_continue(c)
 }
 
 fn is_number(c: char) -> bool {
-    matches!(c, '0'..='9')
+    c.is_ascii_digit()
+}
+
+// Digit separators may only appear between two digits.
+fn valid_separators(src: &str, is_digit: fn(&u8) -> bool) -> bool {
+    let bytes = src.as_bytes();
+
+    bytes.iter().enumerate().all(|(i, &b)| {
+        b != b'_'
+            || (i > 0 && i + 1 < bytes.len() && is_digit(&bytes[i - 1]) && is_digit(&bytes[i + 1]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_count_characters() {
+        let positions = tokenize("var é = \"ü\";\n  é;")
+            .map(|token| (token.line, token.column))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            positions,
+            [
+                (1, 1),
+                (1, 5),
+                (1, 7),
+                (1, 9),
+                (1, 12),
+                (2, 3),
+                (2, 4),
+                (2, 5)
+            ]
+        );
+    }
 }
@@ -0,0 +1,144 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::env::Env;
+use crate::limits::{display_size, LIST_SLOT};
+use crate::native::{define_fn, Args};
+use crate::value::Value;
+use crate::LoxError;
+
+/// Defines the string and conversion natives in `env`.
+pub fn define(env: &mut Env) {
+    define_fn(env, "str", 1, |args| {
+        args.reserve(display_size(&args[0]))?;
+        Ok(Value::Str(args[0].to_string()))
+    });
+    define_fn(env, "num", 1, |args| num(&args[0]));
+    define_fn(env, "type", 1, |args| {
+        Ok(Value::Str(args[0].type_name().into()))
+    });
+    define_fn(env, "len", 1, |args| len(&args[0]));
+
+    define_fn(env, "substr", 3, |args| {
+        let chars: Vec<char> = args.str(0)?.chars().collect();
+        let start = index(args.num(1)?, chars.len())?;
+        let end = index(args.num(2)?, chars.len())?;
+        if start > end {
+            return Err(LoxError::native("String index out of bounds."));
+        }
+        Ok(Value::Str(chars[start..end].iter().collect()))
+    });
+
+    define_fn(env, "index_of", 2, |args| {
+        let haystack = args.str(0)?;
+        let needle = args.str(1)?;
+        let idx = match haystack.find(needle) {
+            Some(byte) => haystack[..byte].chars().count() as f64,
+            None => -1.0,
+        };
+        Ok(Value::Num(idx))
+    });
+
+    define_fn(env, "split", 2, |args| {
+        let s = args.str(0)?;
+        let sep = args.str(1)?;
+        let count = if sep.is_empty() {
+            s.chars().count()
+        } else {
+            s.matches(sep).count() + 1
+        };
+        args.reserve(count * LIST_SLOT + s.len())?;
+
+        let parts = if sep.is_empty() {
+            s.chars().map(|c| Value::Str(c.into())).collect()
+        } else {
+            s.split(sep).map(|p| Value::Str(p.into())).collect()
+        };
+        Ok(list(parts))
+    });
+
+    define_fn(env, "join", 2, |args| {
+        let sep = args.str(1)?;
+        match &args[0] {
+            Value::List(items) => {
+                let items = items.borrow();
+                let seps = items.len().saturating_sub(1) * sep.len();
+                args.reserve(items.iter().map(display_size).sum::<usize>() + seps)?;
+
+                let items: Vec<String> = items.iter().map(Value::to_string).collect();
+                Ok(Value::Str(items.join(sep)))
+            }
+            _ => Err(Args::expected(0, "a list")),
+        }
+    });
+
+    define_fn(env, "upper", 1, |args| {
+        Ok(Value::Str(args.str(0)?.to_uppercase()))
+    });
+
+    define_fn(env, "lower", 1, |args| {
+        Ok(Value::Str(args.str(0)?.to_lowercase()))
+    });
+
+    define_fn(env, "trim", 1, |args| {
+        Ok(Value::Str(args.str(0)?.trim().into()))
+    });
+
+    define_fn(env, "replace", 3, |args| {
+        let s = args.str(0)?;
+        let from = args.str(1)?;
+        let to = args.str(2)?;
+        if from.is_empty() {
+            return Ok(Value::Str(s.into()));
+        }
+        args.reserve(s.len() + s.matches(from).count() * to.len())?;
+        Ok(Value::Str(s.replace(from, to)))
+    });
+
+    define_fn(env, "chars", 1, |args| {
+        let s = args.str(0)?;
+        args.reserve(s.chars().count() * LIST_SLOT + s.len())?;
+        Ok(list(s.chars().map(|c| Value::Str(c.into())).collect()))
+    });
+}
+
+fn list(items: Vec<Value>) -> Value {
+    Value::List(Rc::new(RefCell::new(items)))
+}
+
+fn index(n: f64, len: usize) -> Result<usize, LoxError> {
+    if n.fract() != 0.0 {
+        Err(LoxError::native("String index must be an integer."))
+    } else if n < 0.0 || n > len as f64 {
+        Err(LoxError::native("String index out of bounds."))
+    } else {
+        Ok(n as usize)
+    }
+}
+
+fn num(value: &Value) -> Result<Value, LoxError> {
+    match value {
+        Value::Num(n) => Ok(Value::Num(*n)),
+        Value::Str(s) => {
+            let s = s.trim();
+            let numeric = !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'));
+            match s.parse() {
+                Ok(n) if numeric => Ok(Value::Num(n)),
+                _ => Err(LoxError::native(format!("Invalid number '{}'.", s))),
+            }
+        }
+        _ => Err(Args::expected(0, "a number or string")),
+    }
+}
+
+fn len(value: &Value) -> Result<Value, LoxError> {
+    let len = match value {
+        Value::Str(s) => s.chars().count(),
+        Value::List(list) => list.borrow().len(),
+        Value::Map(map) => map.borrow().len(),
+        _ => return Err(Args::expected(0, "a string, list or map")),
+    };
+    Ok(Value::Num(len as f64))
+}
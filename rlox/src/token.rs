@@ -1,24 +1,35 @@
+use std::borrow::Cow;
 use std::fmt;
 use std::str::FromStr;
 
 use crate::scanner::ScanError;
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Clone)]
 pub struct Token<'a> {
     pub kind: TokenKind<'a>,
     pub line: u64,
+    // Counted in characters from 1, so that multi-byte characters count
+    // once. This is where the token starts, while `line` is where it ends.
+    pub column: u64,
+    // The source text of the token. Empty for ones the scanner makes up,
+    // such as `EOF`.
+    pub lexeme: &'a str,
 }
 
-#[allow(bad_style)]
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[allow(bad_style, clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum TokenKind<'a> {
     // Single-character tokens.
     LEFT_PAREN,
     RIGHT_PAREN,
     LEFT_BRACE,
     RIGHT_BRACE,
+    LEFT_BRACKET,
+    RIGHT_BRACKET,
+    COLON,
     COMMA,
     DOT,
+    DOT_DOT,
     MINUS,
     PLUS,
     SEMICOLON,
@@ -30,6 +41,7 @@ pub enum TokenKind<'a> {
     BANG_EQUAL,
     EQUAL,
     EQUAL_EQUAL,
+    ARROW,
     GREATER,
     GREATER_EQUAL,
     LESS,
@@ -37,29 +49,44 @@ pub enum TokenKind<'a> {
 
     // Literals.
     IDENTIFIER(&'a str),
-    STRING(&'a str),
+    STRING(Cow<'a, str>),
+    // A string segment followed by `${`.
+    INTERPOLATION(Cow<'a, str>),
     NUMBER(f64),
 
     // Keywords.
     AND,
+    AS,
+    BREAK,
+    CATCH,
     CLASS,
+    CONTINUE,
     ELSE,
     FALSE,
+    FINALLY,
+    FROM,
     FUN,
     FOR,
     IF,
+    IMPORT,
+    IN,
     NIL,
     OR,
     PRINT,
     RETURN,
     SUPER,
     THIS,
+    THROW,
     TRUE,
+    TRY,
     VAR,
     WHILE,
 
     WHITESPACE,
-    COMMENT,
+    // A `//` or `/* */` comment, delimiters included.
+    COMMENT(&'a str),
+    // The text of a `///` line, without the slashes.
+    DOC_COMMENT(&'a str),
 
     ERROR(ScanError),
 
@@ -89,18 +116,23 @@ impl<'a> TokenKind<'a> {
 }
 
 impl<'a> Token<'a> {
-    pub fn new(kind: TokenKind, line: u64) -> Token<'_> {
-        Token { kind, line }
+    pub fn new(kind: TokenKind<'_>, line: u64, column: u64) -> Token<'_> {
+        Token {
+            kind,
+            line,
+            column,
+            lexeme: "",
+        }
     }
 
     pub fn is_whitespace(&self) -> bool {
-        matches!(self.kind, COMMENT | WHITESPACE)
+        matches!(self.kind, WHITESPACE)
     }
 }
 
 impl fmt::Display for Token<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.kind {
+        match &self.kind {
             STRING(s) => write!(f, "{}", s),
             NUMBER(s) => write!(f, "{}", s,),
             IDENTIFIER(s) => write!(f, "{}", s),
@@ -117,19 +149,29 @@ impl<'a> FromStr for TokenKind<'a> {
 
         match s {
             "and" => Ok(AND),
+            "as" => Ok(AS),
+            "break" => Ok(BREAK),
+            "catch" => Ok(CATCH),
             "class" => Ok(CLASS),
+            "continue" => Ok(CONTINUE),
             "else" => Ok(ELSE),
             "false" => Ok(FALSE),
+            "finally" => Ok(FINALLY),
+            "from" => Ok(FROM),
             "for" => Ok(FOR),
             "fun" => Ok(FUN),
             "if" => Ok(IF),
+            "import" => Ok(IMPORT),
+            "in" => Ok(IN),
             "nil" => Ok(NIL),
             "or" => Ok(OR),
             "print" => Ok(PRINT),
             "return" => Ok(RETURN),
             "super" => Ok(SUPER),
             "this" => Ok(THIS),
+            "throw" => Ok(THROW),
             "true" => Ok(TRUE),
+            "try" => Ok(TRY),
             "var" => Ok(VAR),
             "while" => Ok(WHILE),
             _ => Err(()),
@@ -148,9 +190,13 @@ impl fmt::Display for TokenKind<'_> {
             RIGHT_PAREN => ")",
             LEFT_BRACE => "{",
             RIGHT_BRACE => "}",
+            LEFT_BRACKET => "[",
+            RIGHT_BRACKET => "]",
             SEMICOLON => ";",
+            COLON => ":",
             COMMA => ",",
             DOT => ".",
+            DOT_DOT => "..",
             MINUS => "-",
             PLUS => "+",
             SLASH => "/",
@@ -159,28 +205,40 @@ impl fmt::Display for TokenKind<'_> {
             BANG_EQUAL => "!=",
             EQUAL => "=",
             EQUAL_EQUAL => "==",
+            ARROW => "=>",
             GREATER => ">",
             GREATER_EQUAL => ">=",
             LESS => "<",
             LESS_EQUAL => "<=",
             AND => "and",
+            AS => "as",
+            BREAK => "break",
+            CATCH => "catch",
             CLASS => "class",
+            CONTINUE => "continue",
             ELSE => "else",
             FALSE => "false",
+            FINALLY => "finally",
+            FROM => "from",
             FUN => "fun",
             FOR => "for",
             IF => "if",
+            IMPORT => "import",
+            IN => "in",
             NIL => "nil",
             OR => "or",
             PRINT => "print",
             RETURN => "return",
             SUPER => "super",
             THIS => "this",
+            THROW => "throw",
             TRUE => "true",
+            TRY => "try",
             VAR => "var",
             WHILE => "while",
             IDENTIFIER(s) => s,
             STRING(s) => s,
+            INTERPOLATION(s) => s,
             EOF => "eof",
             ERROR(_) => "err",
             _ => "%",
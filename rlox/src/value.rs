@@ -1,20 +1,40 @@
+use std::cell::RefCell;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt::{self, Debug, Write};
 use std::rc::Rc;
 
 use crate::ast::Lit;
+use crate::host::HostObject;
+use crate::list::List;
+use crate::map::MapRef;
+use crate::module::Module;
 use crate::{Interpreter, LoxError};
 
 pub trait Callable: Debug + std::fmt::Display {
     fn arity(&self) -> usize;
+
+    /// Variadic callables accept `arity()` or more arguments.
+    fn variadic(&self) -> bool {
+        false
+    }
+
     fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, LoxError>;
 }
 
+/// A Lox runtime value.
+///
+/// Rust values convert into it with `From`, and back out with `TryFrom`,
+/// which fails with a runtime error when the value has the wrong type.
 #[derive(Debug, Clone)]
 pub enum Value {
     Bool(bool),
     Nil,
     Call(Rc<dyn Callable>),
+    Host(Rc<dyn HostObject>),
+    List(List),
+    Map(MapRef),
+    Module(Rc<Module>),
     Num(f64),
     Str(String),
 }
@@ -24,6 +44,33 @@ const TWO_NUMBERS: &str = "Operands must be numbers.";
 const ONE_NUMBER: &str = "Operand must be a number.";
 
 impl Value {
+    pub fn host(object: impl HostObject) -> Value {
+        Value::Host(Rc::new(object))
+    }
+
+    /// The host object this value holds, if it is a `T`.
+    pub fn downcast_host<T: HostObject>(&self) -> Option<&T> {
+        match self {
+            Value::Host(object) => object.downcast_ref(),
+            _ => None,
+        }
+    }
+
+    /// The name `type()` reports for this value.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "bool",
+            Value::Nil => "nil",
+            Value::Call(_) => "function",
+            Value::Host(object) => object.type_name(),
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Module(_) => "module",
+            Value::Num(_) => "number",
+            Value::Str(_) => "string",
+        }
+    }
+
     pub fn add(&self, other: &Self) -> Result<Value, &'static str> {
         match (self, other) {
             (Value::Num(a), Value::Num(b)) => Ok(Value::Num(a + b)),
@@ -53,6 +100,8 @@ impl Value {
         }
     }
 
+    // Not `ops::Neg`, since negating a non-number is a runtime error.
+    #[allow(clippy::should_implement_trait)]
     pub fn neg(self) -> Result<Value, &'static str> {
         match self {
             Value::Num(a) => Ok(Value::Num(-a)),
@@ -105,6 +154,28 @@ impl fmt::Display for Value {
         match self {
             Value::Bool(b) => write!(f, "{}", b),
             Value::Call(callable) => write!(f, "{}", callable),
+            Value::List(list) => print_once(Rc::as_ptr(list).cast(), f, "[...]", |f| {
+                f.write_char('[')?;
+                for (i, value) in list.borrow().iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_char(']')
+            }),
+            Value::Map(map) => print_once(Rc::as_ptr(map).cast(), f, "{...}", |f| {
+                f.write_char('{')?;
+                for (i, (key, value)) in map.borrow().iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", Value::from(key), value)?;
+                }
+                f.write_char('}')
+            }),
+            Value::Host(object) => write!(f, "{}", object),
+            Value::Module(module) => write!(f, "{}", module),
             Value::Nil => f.write_str("nil"),
             Value::Num(n) => {
                 if *n == 0.0 && n.is_sign_negative() {
@@ -117,11 +188,45 @@ impl fmt::Display for Value {
     }
 }
 
+thread_local! {
+    // The collections being printed right now, innermost last.
+    static PRINTING: RefCell<Vec<*const ()>> = const { RefCell::new(vec![]) };
+}
+
+// Prints a collection with `print`, unless it is already being printed
+// further out, as in a list or map that contains itself. Then it prints
+// `placeholder` instead.
+fn print_once(
+    ptr: *const (),
+    f: &mut fmt::Formatter<'_>,
+    placeholder: &str,
+    print: impl FnOnce(&mut fmt::Formatter<'_>) -> fmt::Result,
+) -> fmt::Result {
+    if PRINTING.with(|printing| printing.borrow().contains(&ptr)) {
+        return f.write_str(placeholder);
+    }
+
+    PRINTING.with(|printing| printing.borrow_mut().push(ptr));
+    let res = print(f);
+    PRINTING.with(|printing| printing.borrow_mut().pop());
+
+    res
+}
+
+// Primitives compare by value, while functions and collections compare by
+// identity. Host objects may opt into value equality through `equals`.
+// Only nil, booleans, numbers and strings are hashable, so only they can be
+// used as map keys (see `map::Key`).
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Value::Bool(a), Value::Bool(b)) => a.eq(b),
             (Value::Nil, Value::Nil) => true,
+            (Value::Call(a), Value::Call(b)) => Rc::ptr_eq(a, b),
+            (Value::Host(a), Value::Host(b)) => Rc::ptr_eq(a, b) || a.equals(b.as_ref()),
+            (Value::List(a), Value::List(b)) => Rc::ptr_eq(a, b),
+            (Value::Map(a), Value::Map(b)) => Rc::ptr_eq(a, b),
+            (Value::Module(a), Value::Module(b)) => Rc::ptr_eq(a, b),
             (Value::Num(a), Value::Num(b)) => a.eq(b),
             (Value::Str(a), Value::Str(b)) => a.eq(b),
             _ => false,
@@ -135,6 +240,98 @@ impl From<bool> for Value {
     }
 }
 
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Num(n)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.into())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(opt: Option<T>) -> Self {
+        opt.map_or(Value::Nil, Into::into)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Self {
+        let items = items.into_iter().map(Into::into).collect();
+        Value::List(Rc::new(RefCell::new(items)))
+    }
+}
+
+fn expected(what: &str, value: &Value) -> LoxError {
+    LoxError::native(format!("Expected {} but got {}.", what, value.type_name()))
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = LoxError;
+
+    fn try_from(value: Value) -> Result<Self, LoxError> {
+        match value {
+            Value::Num(n) => Ok(n),
+            _ => Err(expected("a number", &value)),
+        }
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = LoxError;
+
+    fn try_from(value: Value) -> Result<Self, LoxError> {
+        match value {
+            Value::Bool(b) => Ok(b),
+            _ => Err(expected("a boolean", &value)),
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = LoxError;
+
+    fn try_from(value: Value) -> Result<Self, LoxError> {
+        match value {
+            Value::Str(s) => Ok(s),
+            _ => Err(expected("a string", &value)),
+        }
+    }
+}
+
+// `nil` becomes `None`; anything else must convert to `T`.
+impl<T: TryFrom<Value, Error = LoxError>> TryFrom<Value> for Option<T> {
+    type Error = LoxError;
+
+    fn try_from(value: Value) -> Result<Self, LoxError> {
+        match value {
+            Value::Nil => Ok(None),
+            value => T::try_from(value).map(Some),
+        }
+    }
+}
+
+// Copies the list's current elements out, converting each one.
+impl<T: TryFrom<Value, Error = LoxError>> TryFrom<Value> for Vec<T> {
+    type Error = LoxError;
+
+    fn try_from(value: Value) -> Result<Self, LoxError> {
+        match value {
+            Value::List(list) => list.borrow().iter().cloned().map(T::try_from).collect(),
+            _ => Err(expected("a list", &value)),
+        }
+    }
+}
+
 impl From<&Lit> for Value {
     fn from(lit: &Lit) -> Self {
         match lit {
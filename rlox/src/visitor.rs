@@ -8,9 +8,32 @@ pub trait ExprVisitor {
         match expr {
             Expr::Assign(name, expr, line) => self.visit_assign_expr(name, expr, *line),
             Expr::Binary { lhs, op, rhs, line } => self.visit_binary_expr(lhs, *op, rhs, *line),
-            Expr::Call { callee, args, line } => self.visit_call_expr(callee, &args, *line),
+            Expr::Call { callee, args, line } => self.visit_call_expr(callee, args, *line),
+            Expr::Get { object, name, line } => self.visit_get_expr(object, name, *line),
             Expr::Grouping(expr) => self.visit_grouping_expr(expr),
+            Expr::Index {
+                object,
+                index,
+                line,
+            } => self.visit_index_expr(object, index, *line),
+            Expr::Lambda {
+                params, body, line, ..
+            } => self.visit_lambda_expr(params, body, *line),
+            Expr::List(elements) => self.visit_list_expr(elements),
+            Expr::Map(entries, line) => self.visit_map_expr(entries, *line),
             Expr::Literal(lit) => self.visit_literal_expr(lit),
+            Expr::Set {
+                object,
+                name,
+                value,
+                line,
+            } => self.visit_set_expr(object, name, value, *line),
+            Expr::SetIndex {
+                object,
+                index,
+                value,
+                line,
+            } => self.visit_set_index_expr(object, index, value, *line),
             Expr::Logical { lhs, kw, rhs, line } => self.visit_logical_expr(lhs, *kw, rhs, *line),
             Expr::Unary(op, expr, line) => self.visit_unary_expr(*op, expr, *line),
             Expr::Variable(name, line) => self.visit_variable_expr(name, *line),
@@ -23,10 +46,20 @@ pub trait ExprVisitor {
 
     fn visit_call_expr(&mut self, callee: &Expr, args: &[Expr], line: u64) -> Self::Output;
 
+    fn visit_get_expr(&mut self, object: &Expr, name: &str, line: u64) -> Self::Output;
+
     fn visit_grouping_expr(&mut self, expr: &Expr) -> Self::Output {
         self.visit_expr(expr)
     }
 
+    fn visit_index_expr(&mut self, object: &Expr, index: &Expr, line: u64) -> Self::Output;
+
+    fn visit_lambda_expr(&mut self, params: &[String], body: &[Stmt], line: u64) -> Self::Output;
+
+    fn visit_list_expr(&mut self, elements: &[Expr]) -> Self::Output;
+
+    fn visit_map_expr(&mut self, entries: &[(Expr, Expr)], line: u64) -> Self::Output;
+
     fn visit_literal_expr(&mut self, literal: &Lit) -> Self::Output;
 
     fn visit_logical_expr(
@@ -37,6 +70,22 @@ pub trait ExprVisitor {
         line: u64,
     ) -> Self::Output;
 
+    fn visit_set_expr(
+        &mut self,
+        object: &Expr,
+        name: &str,
+        value: &Expr,
+        line: u64,
+    ) -> Self::Output;
+
+    fn visit_set_index_expr(
+        &mut self,
+        object: &Expr,
+        index: &Expr,
+        value: &Expr,
+        line: u64,
+    ) -> Self::Output;
+
     fn visit_unary_expr(&mut self, op: UnOp, rhs: &Expr, line: u64) -> Self::Output;
 
     fn visit_variable_expr(&mut self, name: &str, line: u64) -> Self::Output;
@@ -47,30 +96,90 @@ pub trait StmtVisitor {
 
     fn visit_stmt(&mut self, stmt: &Stmt) -> Self::Output {
         match stmt {
-            Stmt::Block(stmts) => self.visit_block_stmt(&stmts),
+            Stmt::Block(stmts) => self.visit_block_stmt(stmts),
+            Stmt::Break(line) => self.visit_break_stmt(*line),
+            Stmt::Continue(line) => self.visit_continue_stmt(*line),
             Stmt::Expr(expr) => self.visit_expression_stmt(expr),
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => self.visit_for_stmt(
+                initializer.as_deref(),
+                condition.as_ref(),
+                increment.as_ref(),
+                body,
+            ),
+            Stmt::ForIn {
+                name,
+                iterable,
+                range_end,
+                body,
+                line,
+            } => self.visit_for_in_stmt(name, iterable, range_end.as_ref(), body, *line),
             Stmt::Function {
                 name,
                 params,
                 body,
                 line,
+                ..
             } => self.visit_function_stmt(name, params.as_slice(), body, *line),
+            Stmt::Import {
+                path,
+                alias,
+                names,
+                line,
+            } => self.visit_import_stmt(path, alias.as_deref(), names, *line),
             Stmt::Print(expr) => self.visit_print_stmt(expr),
             Stmt::Return(expr, line) => self.visit_return_stmt(expr.as_ref(), *line),
+            Stmt::Throw(expr, line) => self.visit_throw_stmt(expr, *line),
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+            } => self.visit_try_stmt(
+                body,
+                catch
+                    .as_ref()
+                    .map(|(name, handler)| (name.as_str(), handler.as_slice())),
+                finally.as_deref(),
+            ),
             Stmt::Var(name, initializer) => self.visit_var_stmt(name, initializer.as_ref()),
             Stmt::If {
                 condition,
                 then,
                 r#else,
             } => self.visit_if_stmt(condition, then, r#else.as_deref()),
-            Stmt::While(condition, body) => self.visit_while_stmt(condition, body),
+            Stmt::While { condition, body } => self.visit_while_stmt(condition, body),
         }
     }
 
     fn visit_block_stmt(&mut self, stmts: &[Stmt]) -> Self::Output;
 
+    fn visit_break_stmt(&mut self, line: u64) -> Self::Output;
+
+    fn visit_continue_stmt(&mut self, line: u64) -> Self::Output;
+
     fn visit_expression_stmt(&mut self, expr: &Expr) -> Self::Output;
 
+    fn visit_for_stmt(
+        &mut self,
+        initializer: Option<&Stmt>,
+        condition: Option<&Expr>,
+        increment: Option<&Expr>,
+        body: &Stmt,
+    ) -> Self::Output;
+
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &str,
+        iterable: &Expr,
+        range_end: Option<&Expr>,
+        body: &Stmt,
+        line: u64,
+    ) -> Self::Output;
+
     fn visit_function_stmt(
         &mut self,
         name: &str,
@@ -86,10 +195,27 @@ pub trait StmtVisitor {
         r#else: Option<&Stmt>,
     ) -> Self::Output;
 
+    fn visit_import_stmt(
+        &mut self,
+        path: &str,
+        alias: Option<&str>,
+        names: &[String],
+        line: u64,
+    ) -> Self::Output;
+
     fn visit_print_stmt(&mut self, expr: &Expr) -> Self::Output;
 
     fn visit_return_stmt(&mut self, expr: Option<&Expr>, line: u64) -> Self::Output;
 
+    fn visit_throw_stmt(&mut self, expr: &Expr, line: u64) -> Self::Output;
+
+    fn visit_try_stmt(
+        &mut self,
+        body: &[Stmt],
+        catch: Option<(&str, &[Stmt])>,
+        finally: Option<&[Stmt]>,
+    ) -> Self::Output;
+
     fn visit_var_stmt(&mut self, name: &str, initializer: Option<&Expr>) -> Self::Output;
 
     fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> Self::Output;
@@ -0,0 +1,96 @@
+mod common;
+
+use std::convert::TryFrom;
+
+use rlox::{LoxError, Value};
+
+#[test]
+fn call_a_script_function() {
+    let (mut interpreter, out) = common::interpreter();
+    let source = "
+        fun greet(name) {
+          print \"hello \" + name;
+          return len(name);
+        }
+    ";
+    common::run(&mut interpreter, source).unwrap();
+
+    let greet = interpreter.get_global("greet").unwrap();
+    let res = interpreter.call(&greet, &["lox".into()]).unwrap();
+    assert_eq!(res, Value::Num(3.0));
+    assert_eq!(out.take(), "hello lox\n");
+}
+
+#[test]
+fn call_checks_arity_and_callee() {
+    let (mut interpreter, _) = common::interpreter();
+    common::run(&mut interpreter, "fun f(a) {} var x = 1;").unwrap();
+
+    let f = interpreter.get_global("f").unwrap();
+    let res = interpreter.call(&f, &[]).map(|_| ());
+    assert_eq!(
+        common::runtime_error(res),
+        "Expected 1 arguments but got 0."
+    );
+
+    let x = interpreter.get_global("x").unwrap();
+    let res = interpreter.call(&x, &[]).map(|_| ());
+    assert_eq!(
+        common::runtime_error(res),
+        "Can only call functions and classes."
+    );
+}
+
+#[test]
+fn call_passes_script_errors_back() {
+    let (mut interpreter, _) = common::interpreter();
+    common::run(&mut interpreter, "fun fail(x) { throw x; }").unwrap();
+
+    let fail = interpreter.get_global("fail").unwrap();
+    match interpreter.call(&fail, &["oops".into()]) {
+        Err(LoxError::Throw(value, _)) => assert_eq!(value, Value::from("oops")),
+        res => panic!("expected a throw, got {:?}", res),
+    }
+}
+
+#[test]
+fn get_global_sees_builtins_but_not_locals() {
+    let (mut interpreter, _) = common::interpreter();
+    common::run(&mut interpreter, "{ var local = 1; }").unwrap();
+
+    assert!(interpreter.get_global("clock").is_some());
+    assert!(interpreter.get_global("local").is_none());
+    assert!(interpreter.get_global("missing").is_none());
+}
+
+#[test]
+fn values_from_rust() {
+    assert_eq!(Value::from(true), Value::Bool(true));
+    assert_eq!(Value::from(1.5), Value::Num(1.5));
+    assert_eq!(Value::from("a"), Value::Str("a".into()));
+    assert_eq!(Value::from(None::<f64>), Value::Nil);
+    assert_eq!(Value::from(Some("a")).to_string(), "a");
+    assert_eq!(Value::from(vec![1.0, 2.0]).to_string(), "[1, 2]");
+}
+
+#[test]
+fn values_into_rust() {
+    let (mut interpreter, _) = common::interpreter();
+    common::run(&mut interpreter, "var xs = [\"a\", nil, \"c\"]; var n = 2;").unwrap();
+
+    let xs = interpreter.get_global("xs").unwrap();
+    let xs = Vec::<Option<String>>::try_from(xs).unwrap();
+    assert_eq!(xs, [Some("a".into()), None, Some("c".into())]);
+
+    let n = interpreter.get_global("n").unwrap();
+    assert_eq!(f64::try_from(n.clone()).unwrap(), 2.0);
+
+    let res = bool::try_from(n).map(|_| ());
+    assert_eq!(
+        common::runtime_error(res),
+        "Expected a boolean but got number."
+    );
+
+    let res = Vec::<String>::try_from(Value::from(vec![Value::Nil])).map(|_| ());
+    assert_eq!(common::runtime_error(res), "Expected a string but got nil.");
+}
@@ -0,0 +1,48 @@
+// Each test file uses only some of these.
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use rlox::{Interpreter, LoxError};
+
+/// Collects what a script prints, while the interpreter owns the writer.
+#[derive(Clone, Default)]
+pub struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Output {
+    pub fn take(&self) -> String {
+        String::from_utf8(self.0.take()).unwrap()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An interpreter whose `print` output goes to the returned `Output`.
+pub fn interpreter() -> (Interpreter, Output) {
+    let out = Output::default();
+    let mut interpreter = Interpreter::new();
+    interpreter.set_output(out.clone());
+    (interpreter, out)
+}
+
+pub fn run(interpreter: &mut Interpreter, source: &str) -> Result<(), LoxError> {
+    interpreter.interpret(rlox::parse(source)?)
+}
+
+/// The message of a runtime error, or panics with whatever came instead.
+pub fn runtime_error(res: Result<(), LoxError>) -> String {
+    match res {
+        Err(LoxError::Runtime(msg, _)) => msg,
+        res => panic!("expected a runtime error, got {:?}", res),
+    }
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use rlox::{DeterministicHost, Host};
+
+const SOURCE: &str = "
+    print clock();
+    print clock();
+    var xs = [];
+    for (var i = 0; i < 3; i = i + 1) xs.push(random());
+    print xs;
+";
+
+fn run_deterministic(seed: u64) -> String {
+    let (mut interpreter, out) = common::interpreter();
+    interpreter.set_host(DeterministicHost::new(seed));
+    common::run(&mut interpreter, SOURCE).unwrap();
+    out.take()
+}
+
+#[test]
+fn clock_ticks_from_zero() {
+    let output = run_deterministic(0);
+    assert!(output.starts_with("0\n0.001\n"), "{}", output);
+}
+
+#[test]
+fn runs_with_the_same_seed_match() {
+    assert_eq!(run_deterministic(7), run_deterministic(7));
+    assert_ne!(run_deterministic(7), run_deterministic(8));
+}
+
+#[test]
+fn seed_restarts_the_sequence() {
+    let (mut interpreter, out) = common::interpreter();
+    interpreter.set_host(DeterministicHost::default());
+
+    let source = "seed(42); var a = random(); seed(42); print a == random();";
+    common::run(&mut interpreter, source).unwrap();
+    assert_eq!(out.take(), "true\n");
+}
+
+#[test]
+fn random_stays_in_range() {
+    let mut host = DeterministicHost::new(1);
+    for _ in 0..1000 {
+        let n = host.random();
+        assert!((0.0..1.0).contains(&n), "{}", n);
+    }
+}
+
+/// A host of the embedder's own, with a clock that never moves.
+struct Frozen;
+
+impl Host for Frozen {
+    fn clock(&mut self) -> f64 {
+        100.0
+    }
+
+    fn random(&mut self) -> f64 {
+        0.5
+    }
+
+    fn seed(&mut self, _: u64) {}
+}
+
+#[test]
+fn custom_host() {
+    let (mut interpreter, out) = common::interpreter();
+    interpreter.set_host(Frozen);
+
+    common::run(&mut interpreter, "print clock(); seed(1); print random();").unwrap();
+    assert_eq!(out.take(), "100\n0.5\n");
+}
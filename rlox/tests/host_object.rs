@@ -0,0 +1,131 @@
+mod common;
+
+use std::cell::Cell;
+use std::fmt;
+
+use rlox::{Args, HostObject, LoxError, Value};
+
+/// A counter scripts can read, reset and step.
+#[derive(Debug, Default)]
+struct Counter {
+    count: Cell<f64>,
+}
+
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<counter {}>", self.count.get())
+    }
+}
+
+impl HostObject for Counter {
+    fn type_name(&self) -> &'static str {
+        "counter"
+    }
+
+    fn get(&self, name: &str) -> Option<Value> {
+        match name {
+            "count" => Some(Value::Num(self.count.get())),
+            _ => None,
+        }
+    }
+
+    fn set(&self, name: &str, value: Value) -> Result<(), LoxError> {
+        match name {
+            "count" => {
+                self.count.set(Args::from(vec![value]).num(0)?);
+                Ok(())
+            }
+            _ => Err(LoxError::native(format!("Can't set '{}'.", name))),
+        }
+    }
+
+    fn method_arity(&self, name: &str) -> Option<usize> {
+        match name {
+            "step" => Some(1),
+            _ => None,
+        }
+    }
+
+    fn call_method(&self, _name: &str, args: Args) -> Result<Value, LoxError> {
+        self.count.set(self.count.get() + args.num(0)?);
+        Ok(Value::Num(self.count.get()))
+    }
+
+    fn equals(&self, other: &dyn HostObject) -> bool {
+        other
+            .downcast_ref::<Counter>()
+            .is_some_and(|other| other.count == self.count)
+    }
+}
+
+fn setup() -> (rlox::Interpreter, common::Output) {
+    let (mut interpreter, out) = common::interpreter();
+    interpreter.define_global("counter", Value::host(Counter::default()));
+    interpreter.register_fn("make_counter", 0, |_| Ok(Value::host(Counter::default())));
+    (interpreter, out)
+}
+
+#[test]
+fn fields_and_methods() {
+    let (mut interpreter, out) = setup();
+    let source = "
+        counter.step(2);
+        var step = counter.step;
+        step(3);
+        print counter.count;
+        counter.count = 10;
+        print counter;
+        print type(counter);
+    ";
+
+    common::run(&mut interpreter, source).unwrap();
+    assert_eq!(out.take(), "5\n<counter 10>\ncounter\n");
+}
+
+#[test]
+fn errors_from_the_object() {
+    let (mut interpreter, _) = setup();
+
+    let res = common::run(&mut interpreter, "counter.count = \"a\";");
+    assert_eq!(common::runtime_error(res), "Argument 1 must be a number.");
+
+    let res = common::run(&mut interpreter, "counter.name = 1;");
+    assert_eq!(common::runtime_error(res), "Can't set 'name'.");
+
+    let res = common::run(&mut interpreter, "counter.reset();");
+    assert_eq!(common::runtime_error(res), "Undefined property 'reset'.");
+
+    let res = common::run(&mut interpreter, "counter.step(1, 2);");
+    assert_eq!(
+        common::runtime_error(res),
+        "Expected 1 arguments but got 2."
+    );
+}
+
+#[test]
+fn equality_uses_equals() {
+    let (mut interpreter, out) = setup();
+    let source = "
+        var a = make_counter();
+        var b = make_counter();
+        print a == b;
+        b.step(1);
+        print a == b;
+        print a == a;
+        print a == 0;
+    ";
+
+    common::run(&mut interpreter, source).unwrap();
+    assert_eq!(out.take(), "true\nfalse\ntrue\nfalse\n");
+}
+
+#[test]
+fn downcast_back_to_rust() {
+    let (mut interpreter, _) = setup();
+    common::run(&mut interpreter, "counter.step(4);").unwrap();
+
+    let value = interpreter.get_global("counter").unwrap();
+    let counter = value.downcast_host::<Counter>().unwrap();
+    assert_eq!(counter.count.get(), 4.0);
+    assert!(Value::Nil.downcast_host::<Counter>().is_none());
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use std::thread;
+use std::time::Duration;
+
+use rlox::{LoxError, Value};
+
+fn limit_error(res: Result<(), LoxError>) -> (String, u64) {
+    match res {
+        Err(LoxError::Limit(msg, line)) => (msg, line),
+        res => panic!("expected a limit error, got {:?}", res),
+    }
+}
+
+#[test]
+fn step_limit_stops_at_the_current_line() {
+    let (mut interpreter, _) = common::interpreter();
+    interpreter.set_step_limit(Some(100));
+
+    let res = common::run(&mut interpreter, "var i = 0;\nwhile (i >= 0)\n  i = i + 1;");
+    assert_eq!(limit_error(res), ("Step limit exceeded.".into(), 3));
+
+    // Each run gets the full allowance again.
+    common::run(&mut interpreter, "for (var i = 0; i < 10; i = i + 1) {}").unwrap();
+}
+
+#[test]
+fn step_limit_is_not_catchable() {
+    let (mut interpreter, _) = common::interpreter();
+    interpreter.set_step_limit(Some(50));
+
+    let source = "try {\n  while (true) {}\n} catch (e) {\n  print e;\n}";
+    let (msg, _) = limit_error(common::run(&mut interpreter, source));
+    assert_eq!(msg, "Step limit exceeded.");
+}
+
+#[test]
+fn call_depth_limit() {
+    let (mut interpreter, _) = common::interpreter();
+    interpreter.set_max_call_depth(10);
+
+    let source = "fun f(n) {\n  if (n > 0) f(n - 1);\n}\nf(9);\nf(10);";
+    let res = common::run(&mut interpreter, source);
+    assert_eq!(common::runtime_error(res), "Stack overflow.");
+}
+
+#[test]
+fn heap_limit_stops_before_allocating() {
+    let (mut interpreter, _) = common::interpreter();
+    interpreter.set_heap_limit(Some(1000));
+
+    let source = "var s = \"x\";\nwhile (true)\n  s = s + s;";
+    assert_eq!(
+        limit_error(common::run(&mut interpreter, source)),
+        ("Out of memory.".into(), 3)
+    );
+
+    let res = common::run(
+        &mut interpreter,
+        "var xs = [];\nfor (var i = 0; i < 1000; i = i + 1)\n  xs.push(i);",
+    );
+    assert_eq!(limit_error(res).0, "Out of memory.");
+}
+
+#[test]
+fn natives_reserve_before_allocating() {
+    let (mut interpreter, _) = common::interpreter();
+    interpreter.set_heap_limit(Some(1000));
+    interpreter.register_fn("big", 1, |args| {
+        let len = args.num(0)? as usize;
+        args.reserve(len)?;
+        Ok(Value::Str("x".repeat(len)))
+    });
+
+    common::run(&mut interpreter, "big(100);").unwrap();
+
+    let res = common::run(&mut interpreter, "print 1;\nbig(1000000);");
+    assert_eq!(limit_error(res), ("Out of memory.".into(), 2));
+
+    let res = common::run(&mut interpreter, "replace(big(100), \"x\", big(100));");
+    assert_eq!(limit_error(res).0, "Out of memory.");
+}
+
+#[test]
+fn interrupt_before_a_run_stops_it() {
+    let (mut interpreter, out) = common::interpreter();
+    interpreter.interrupt_handle().interrupt();
+
+    let res = common::run(&mut interpreter, "print 1;");
+    assert_eq!(limit_error(res).0, "Interrupted.");
+    assert_eq!(out.take(), "");
+
+    // The interrupt was used up.
+    common::run(&mut interpreter, "print 2;").unwrap();
+    assert_eq!(out.take(), "2\n");
+}
+
+#[test]
+fn interrupt_from_another_thread() {
+    let (mut interpreter, _) = common::interpreter();
+    let handle = interpreter.interrupt_handle();
+
+    let interrupter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.interrupt();
+    });
+
+    let res = common::run(&mut interpreter, "var i = 0;\nwhile (true)\n  i = i + 1;");
+    interrupter.join().unwrap();
+    assert_eq!(limit_error(res), ("Interrupted.".into(), 3));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use rlox::{Args, Value};
+
+#[test]
+fn register_fn_defines_a_global() {
+    let (mut interpreter, out) = common::interpreter();
+    interpreter.register_fn("hypot", 2, |args| {
+        Ok(Value::Num(args.num(0)?.hypot(args.num(1)?)))
+    });
+
+    common::run(&mut interpreter, "print hypot(3, 4);").unwrap();
+    assert_eq!(out.take(), "5\n");
+}
+
+#[test]
+fn register_fn_checks_arity() {
+    let (mut interpreter, _) = common::interpreter();
+    interpreter.register_fn("one", 1, |_| Ok(Value::Nil));
+
+    let res = common::run(&mut interpreter, "one(1, 2);");
+    assert_eq!(
+        common::runtime_error(res),
+        "Expected 1 arguments but got 2."
+    );
+}
+
+#[test]
+fn variadic_fn_takes_extra_arguments() {
+    let (mut interpreter, out) = common::interpreter();
+    interpreter.register_variadic_fn("sum", 1, |args| {
+        let mut total = 0.0;
+        for i in 0..args.len() {
+            total += args.num(i)?;
+        }
+        Ok(Value::Num(total))
+    });
+
+    common::run(&mut interpreter, "print sum(1); print sum(1, 2, 3);").unwrap();
+    assert_eq!(out.take(), "1\n6\n");
+
+    let res = common::run(&mut interpreter, "sum();");
+    assert_eq!(
+        common::runtime_error(res),
+        "Expected at least 1 arguments but got 0."
+    );
+}
+
+#[test]
+fn args_report_the_wrong_argument() {
+    let (mut interpreter, _) = common::interpreter();
+    interpreter.register_fn("greet", 2, |args| {
+        let greeting = if args.bool(1)? { "Hi" } else { "Hello" };
+        Ok(Value::Str(format!("{}, {}!", greeting, args.str(0)?)))
+    });
+
+    let res = common::run(&mut interpreter, "greet(\"Ann\", 1);");
+    assert_eq!(common::runtime_error(res), "Argument 2 must be a boolean.");
+
+    let res = common::run(&mut interpreter, "greet(nil, true);");
+    assert_eq!(common::runtime_error(res), "Argument 1 must be a string.");
+}
+
+#[test]
+fn args_deref_to_values() {
+    let args = Args::from(vec![Value::Num(1.0), Value::Nil]);
+
+    assert_eq!(args.len(), 2);
+    assert_eq!(args[1], Value::Nil);
+    assert_eq!(args.into_vec(), vec![Value::Num(1.0), Value::Nil]);
+}
@@ -0,0 +1,126 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+use rlox::is_incomplete;
+
+/// Feeds `input` to the REPL line by line.
+fn repl(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .args(args)
+        // Keeps the REPL from writing a history file.
+        .env_remove("HOME")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn incomplete_input() {
+    assert!(is_incomplete("fun f() {"));
+    assert!(is_incomplete("print [1,"));
+    assert!(is_incomplete("print (1 +"));
+    assert!(is_incomplete("print \"abc"));
+    assert!(is_incomplete("print \"a ${1 + "));
+    assert!(is_incomplete("/* a /* b */"));
+
+    assert!(!is_incomplete("print 1;"));
+    assert!(!is_incomplete("print 1"));
+    assert!(!is_incomplete("{ print \"}\"; }"));
+    assert!(!is_incomplete("// {"));
+    assert!(!is_incomplete("}"));
+}
+
+#[test]
+fn echoes_values_and_keeps_state() {
+    let output = repl(&[], "1 + 2\nvar x = 10;\nx * 2\nnil\nprint \"hi\";\n");
+
+    assert_eq!(stdout(&output), "3\n20\nhi\n");
+    assert_eq!(stderr(&output), "");
+    assert!(output.status.success());
+}
+
+#[test]
+fn continues_unfinished_input() {
+    let output = repl(&[], "fun f() {\n  return \"done\";\n}\nf()\n/* a\nb */ 1\n");
+
+    assert_eq!(stdout(&output), "done\n1\n");
+}
+
+#[test]
+fn reports_errors_like_a_script_run() {
+    let input = "print;\nprint -\"a\";\nthrow \"oops\";\nprint \"still here\";\n";
+    let output = repl(&[], input);
+
+    assert_eq!(
+        stderr(&output),
+        "[line 1] Error at ';': Expect expression.\n\
+         Operand must be a number.\n[line 1]\n\
+         Uncaught exception: oops\n[line 1]\n"
+    );
+    assert_eq!(stdout(&output), "still here\n");
+}
+
+#[test]
+fn commands() {
+    let input = ":help\n:ast 1 + 2 * 3\n:disasm 1\n:nope\n";
+    let output = repl(&[], input);
+    let out = stdout(&output);
+
+    assert!(out.starts_with("Enter declarations"), "{}", out);
+    assert!(out.contains("(+ 1.0 (* 2.0 3.0))\n"), "{}", out);
+    assert!(out.contains("The bytecode VM is not part of this build.\n"));
+    assert!(out.ends_with("Unknown command. Try :help.\n"), "{}", out);
+}
+
+#[test]
+fn reset_forgets_globals() {
+    let output = repl(&[], "var x = 1;\n:reset\nx\n");
+
+    assert_eq!(stdout(&output), "");
+    assert_eq!(stderr(&output), "Undefined variable 'x'.\n[line 1]\n");
+}
+
+#[test]
+fn load_runs_a_file_in_the_session() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../test/function/parameters.lox"
+    );
+    let output = repl(
+        &[],
+        &format!(":load {}\nf2(1, 2)\n:load /no/such/file\n", path),
+    );
+    let out = stdout(&output);
+
+    assert!(out.ends_with("3\n"), "{}", out);
+    assert!(stderr(&output).starts_with("Could not read '/no/such/file'"));
+}
+
+#[test]
+fn quit_and_exit() {
+    let output = repl(&[], "print 1;\n:quit\nprint 2;\n");
+    assert_eq!(stdout(&output), "1\n");
+    assert!(output.status.success());
+
+    let output = repl(&["--allow-io"], "exit(3);\nprint 2;\n");
+    assert_eq!(stdout(&output), "");
+    assert_eq!(output.status.code(), Some(3));
+}
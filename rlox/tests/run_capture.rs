@@ -0,0 +1,46 @@
+use rlox::{run_capture, LoxError};
+
+#[test]
+fn captures_print_output() {
+    let (out, res) = run_capture("print 1 + 2; print \"a\" + \"b\";");
+
+    assert!(res.is_ok());
+    assert_eq!(out, "3\nab\n");
+}
+
+#[test]
+fn keeps_output_before_a_runtime_error() {
+    let (out, res) = run_capture("print \"before\";\nprint -\"a\";\nprint \"after\";");
+
+    assert_eq!(out, "before\n");
+    match res {
+        Err(LoxError::Runtime(msg, line)) => {
+            assert_eq!(msg, "Operand must be a number.");
+            assert_eq!(line, 2);
+        }
+        res => panic!("expected a runtime error, got {:?}", res),
+    }
+}
+
+#[test]
+fn reports_compile_errors_without_running() {
+    let (out, res) = run_capture("print \"never\";\nprint ;");
+
+    assert_eq!(out, "");
+    match res {
+        Err(LoxError::Compile(msg)) => {
+            assert_eq!(msg, "[line 2] Error at ';': Expect expression.")
+        }
+        res => panic!("expected a compile error, got {:?}", res),
+    }
+}
+
+#[test]
+fn runs_are_independent() {
+    let (_, res) = run_capture("var x = 1;");
+    assert!(res.is_ok());
+
+    let (out, res) = run_capture("print x;");
+    assert_eq!(out, "");
+    assert!(matches!(res, Err(LoxError::Runtime(msg, 1)) if msg == "Undefined variable 'x'."));
+}
@@ -448,6 +448,38 @@ void _defineTestSuites() {
     'test/expressions': 'skip',
   };
 
+  // Language extensions only rlox has. It runs as chap10_functions.
+  var rloxOnly = {
+    'test/break': 'skip',
+    'test/comments/doc_comments.lox': 'skip',
+    'test/comments/nested_block.lox': 'skip',
+    'test/comments/unterminated_block.lox': 'skip',
+    'test/continue': 'skip',
+    'test/for_in': 'skip',
+    'test/identifier': 'skip',
+    'test/import': 'skip',
+    'test/interpolation': 'skip',
+    'test/io': 'skip',
+    'test/lambda': 'skip',
+    'test/list': 'skip',
+    'test/map': 'skip',
+    'test/math': 'skip',
+    'test/number/separator_before_exponent.lox': 'skip',
+    'test/number/separators.lox': 'skip',
+    'test/string/conversion.lox': 'skip',
+    'test/string/escapes.lox': 'skip',
+    'test/string/inspect.lox': 'skip',
+    'test/string/invalid_escape.lox': 'skip',
+    'test/string/invalid_number.lox': 'skip',
+    'test/string/invalid_unicode_escape.lox': 'skip',
+    'test/string/not_a_string.lox': 'skip',
+    'test/string/substr_out_of_bounds.lox': 'skip',
+    'test/string/transform.lox': 'skip',
+    'test/string/unicode_escape.lox': 'skip',
+    'test/string/unicode_escape_out_of_range.lox': 'skip',
+    'test/try': 'skip',
+  };
+
   // JVM doesn't correctly implement IEEE equality on boxed doubles.
   var javaNaNEquality = {
     'test/number/nan_equality.lox': 'skip',
@@ -592,6 +624,7 @@ void _defineTestSuites() {
   java('jlox', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...javaNaNEquality,
     ...noJavaLimits,
   });
@@ -619,6 +652,7 @@ void _defineTestSuites() {
   java('chap08_statements', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...javaNaNEquality,
     ...noJavaLimits,
     ...noJavaFunctions,
@@ -637,6 +671,7 @@ void _defineTestSuites() {
   java('chap09_control', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...javaNaNEquality,
     ...noJavaLimits,
     ...noJavaFunctions,
@@ -656,6 +691,7 @@ void _defineTestSuites() {
   java('chap11_resolving', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...javaNaNEquality,
     ...noJavaLimits,
     ...noJavaClasses,
@@ -664,6 +700,7 @@ void _defineTestSuites() {
   java('chap12_classes', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...noJavaLimits,
     ...javaNaNEquality,
 
@@ -680,6 +717,7 @@ void _defineTestSuites() {
   java('chap13_inheritance', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...javaNaNEquality,
     ...noJavaLimits,
   });
@@ -687,6 +725,7 @@ void _defineTestSuites() {
   c('clox', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
   });
 
   c('chap17_compiling', {
@@ -716,6 +755,7 @@ void _defineTestSuites() {
   c('chap21_global', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...noCControlFlow,
     ...noCFunctions,
     ...noCClasses,
@@ -739,6 +779,7 @@ void _defineTestSuites() {
   c('chap22_local', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...noCControlFlow,
     ...noCFunctions,
     ...noCClasses,
@@ -747,6 +788,7 @@ void _defineTestSuites() {
   c('chap23_jumping', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...noCFunctions,
     ...noCClasses,
   });
@@ -754,6 +796,7 @@ void _defineTestSuites() {
   c('chap24_calls', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...noCClasses,
 
     // No closures.
@@ -770,18 +813,21 @@ void _defineTestSuites() {
   c('chap25_closures', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...noCClasses,
   });
 
   c('chap26_garbage', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...noCClasses,
   });
 
   c('chap27_classes', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...noCInheritance,
 
     // No methods.
@@ -804,16 +850,19 @@ void _defineTestSuites() {
   c('chap28_methods', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
     ...noCInheritance,
   });
 
   c('chap29_superclasses', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
   });
 
   c('chap30_optimization', {
     'test': 'pass',
     ...earlyChapters,
+    ...rloxOnly,
   });
 }